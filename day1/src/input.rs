@@ -0,0 +1,99 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::num::ParseIntError;
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    // Line numbers are 1-based, like in an editor
+    BadNumber {
+        lineno: usize,
+        line: String,
+        source: ParseIntError,
+    },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "Failed to read input: {}", e),
+            ReadError::BadNumber { lineno, line, source } => {
+                write!(f, "Line {}: cannot parse \"{}\" as a number: {}", lineno, line, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::BadNumber { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+// One number per line, with an optional leading + or -.
+// Blank lines and surrounding whitespace are ignored.
+pub fn read_numbers<R: BufRead>(reader: R) -> Result<Vec<i64>, ReadError> {
+    let mut numbers = Vec::new();
+    for (i, readline) in reader.lines().enumerate() {
+        let line = readline?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match trimmed.parse::<i64>() {
+            Ok(n) => numbers.push(n),
+            Err(source) => {
+                return Err(ReadError::BadNumber {
+                    lineno: i + 1,
+                    line: trimmed.to_string(),
+                    source,
+                })
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+// Reads from stdin if the path is "-"
+pub fn read_line_file(path: &str) -> Result<Vec<i64>, ReadError> {
+    if path == "-" {
+        let stdin = io::stdin();
+        let lock = stdin.lock();
+        read_numbers(lock)
+    } else {
+        read_numbers(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_numbers, ReadError};
+
+    #[test]
+    fn test_read_numbers() {
+        let input = "1721\n\n  +979\n-366 \r\n\n";
+        assert_eq!(read_numbers(input.as_bytes()).unwrap(), vec![1721, 979, -366]);
+    }
+
+    #[test]
+    fn test_bad_number() {
+        let input = "1721\n\n97x9\n";
+        match read_numbers(input.as_bytes()) {
+            Err(ReadError::BadNumber { lineno, line, .. }) => {
+                assert_eq!(lineno, 3);
+                assert_eq!(line, "97x9");
+            }
+            other => panic!("Expected BadNumber, got {:?}", other),
+        }
+    }
+}
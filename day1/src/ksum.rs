@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::vec;
+
+// Whether two solutions picking the same values at different positions count
+// as different solutions. With input [5, 5, 5] and target 10, `ByIndex` gives
+// (0, 1), (0, 2) and (1, 2), while `ByValue` only gives (0, 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplicity {
+    ByIndex,
+    ByValue,
+}
+
+// Returns every combination of `k` distinct indices i1 < i2 < ... < ik into
+// `numbers` whose values sum to `target`, in lexicographic order.
+// The last two indices are found by hashing, so k=2 runs in O(n) and k=3 in
+// O(n^2) (plus the size of the output), instead of O(n^k) for the naive loops.
+pub fn k_sum(numbers: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    Solutions::new(numbers, k, target, Multiplicity::ByIndex).collect()
+}
+
+// Lazy version of `k_sum`. Only the solutions sharing the current first k-2
+// indices are buffered at any time.
+pub struct Solutions<'a> {
+    numbers: &'a [i64],
+    target: i64,
+    multiplicity: Multiplicity,
+    // The first k-2 indices, advanced like an odometer
+    prefix: Vec<usize>,
+    // Number of indices after the prefix: 2, or less if k < 2
+    taillen: usize,
+    // Solutions for the current prefix not yet yielded
+    pending: vec::IntoIter<Vec<usize>>,
+    // Sorted values of solutions yielded so far, when deduplicating by value
+    seen: HashSet<Vec<i64>>,
+    exhausted: bool,
+}
+
+impl<'a> Solutions<'a> {
+    pub fn new(numbers: &'a [i64], k: usize, target: i64, multiplicity: Multiplicity) -> Self {
+        let prefixlen = k.saturating_sub(2);
+        let mut solutions = Solutions {
+            numbers,
+            target,
+            multiplicity,
+            prefix: (0..prefixlen).collect(),
+            taillen: k - prefixlen,
+            pending: Vec::new().into_iter(),
+            seen: HashSet::new(),
+            exhausted: k > numbers.len(),
+        };
+        if !solutions.exhausted {
+            solutions.pending = solutions.prefix_solutions().into_iter();
+        }
+        solutions
+    }
+
+    // All solutions starting with the current prefix. Sums are taken in i128
+    // so that no combination of i64 values can overflow.
+    fn prefix_solutions(&self) -> Vec<Vec<usize>> {
+        let start = self.prefix.last().map_or(0, |&i| i + 1);
+        let remaining = self.target as i128 - self.prefix.iter().map(|&i| self.numbers[i] as i128).sum::<i128>();
+        let tails: Vec<Vec<usize>> = match self.taillen {
+            0 => {
+                if remaining == 0 {
+                    vec![Vec::new()]
+                } else {
+                    Vec::new()
+                }
+            }
+            1 => (start..self.numbers.len())
+                .filter(|&i| self.numbers[i] as i128 == remaining)
+                .map(|i| vec![i])
+                .collect(),
+            _ => two_sum(self.numbers, start, remaining)
+                .into_iter()
+                .map(|(i, j)| vec![i, j])
+                .collect(),
+        };
+        tails
+            .into_iter()
+            .map(|tail| {
+                let mut combination = self.prefix.clone();
+                combination.extend(tail);
+                combination
+            })
+            .collect()
+    }
+
+    // Moves the prefix to the next combination of indices, leaving room
+    // for the tail indices after it. Returns false when there is none.
+    fn advance_prefix(&mut self) -> bool {
+        let n = self.numbers.len();
+        let m = self.prefix.len();
+        for pos in (0..m).rev() {
+            // Highest index allowed at this position
+            let limit = n - self.taillen - (m - pos);
+            if self.prefix[pos] < limit {
+                self.prefix[pos] += 1;
+                for next in (pos + 1)..m {
+                    self.prefix[next] = self.prefix[next - 1] + 1;
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn is_new(&mut self, combination: &[usize]) -> bool {
+        match self.multiplicity {
+            Multiplicity::ByIndex => true,
+            Multiplicity::ByValue => {
+                let mut values: Vec<i64> = combination.iter().map(|&i| self.numbers[i]).collect();
+                values.sort_unstable();
+                self.seen.insert(values)
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(combination) = self.pending.next() {
+                if self.is_new(&combination) {
+                    return Some(combination);
+                }
+                continue;
+            }
+            if self.exhausted || !self.advance_prefix() {
+                self.exhausted = true;
+                return None;
+            }
+            self.pending = self.prefix_solutions().into_iter();
+        }
+    }
+}
+
+// Whether a search had no, exactly one or several solutions.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    NoSolution,
+    Unique(Vec<usize>),
+    Multiple(Vec<Vec<usize>>),
+}
+
+impl Outcome {
+    pub fn from_solutions<I: Iterator<Item = Vec<usize>>>(mut solutions: I) -> Outcome {
+        let first = match solutions.next() {
+            None => return Outcome::NoSolution,
+            Some(s) => s,
+        };
+        match solutions.next() {
+            None => Outcome::Unique(first),
+            Some(second) => {
+                let mut all = vec![first, second];
+                all.extend(solutions);
+                Outcome::Multiple(all)
+            }
+        }
+    }
+
+    // Process exit code, so scripts can tell the outcomes apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Outcome::Unique(_) => 0,
+            Outcome::NoSolution => 1,
+            Outcome::Multiple(_) => 2,
+        }
+    }
+
+    pub fn solutions(&self) -> &[Vec<usize>] {
+        match self {
+            Outcome::NoSolution => &[],
+            Outcome::Unique(s) => std::slice::from_ref(s),
+            Outcome::Multiple(v) => v,
+        }
+    }
+}
+
+// Single pass over the numbers from `start`, keeping a map from each value
+// seen so far to the indices it was seen at. Every earlier index holding the
+// complement of the current number forms a pair with it. A complement
+// outside the i64 range cannot have been seen.
+fn two_sum(numbers: &[i64], start: usize, target: i128) -> Vec<(usize, usize)> {
+    let mut seen: HashMap<i64, Vec<usize>> = HashMap::new();
+    let mut found: Vec<(usize, usize)> = Vec::new();
+    for (j, &n) in numbers.iter().enumerate().skip(start) {
+        let complement = i64::try_from(target - n as i128).ok();
+        if let Some(indices) = complement.and_then(|c| seen.get(&c)) {
+            found.extend(indices.iter().map(|&i| (i, j)));
+        }
+        seen.entry(n).or_default().push(j);
+    }
+    found.sort_unstable();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{k_sum, Multiplicity, Outcome, Solutions};
+
+    const EXAMPLE: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    #[test]
+    fn test_two_sum() {
+        assert_eq!(k_sum(&EXAMPLE, 2, 2020), vec![vec![0, 3]]);
+    }
+
+    #[test]
+    fn test_three_sum() {
+        assert_eq!(k_sum(&EXAMPLE, 3, 2020), vec![vec![1, 2, 4]]);
+    }
+
+    #[test]
+    fn test_four_sum() {
+        let numbers = [1, 2, 3, 4, 5, 6];
+        assert_eq!(
+            k_sum(&numbers, 4, 12),
+            vec![vec![0, 1, 2, 5], vec![0, 1, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_by_index() {
+        let numbers = [5, 5, 5, 0];
+        assert_eq!(
+            k_sum(&numbers, 2, 10),
+            vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(k_sum(&numbers, 3, 10).len(), 3);
+    }
+
+    #[test]
+    fn test_duplicates_by_value() {
+        let numbers = [5, 5, 5, 0, 10];
+        let solutions: Vec<_> = Solutions::new(&numbers, 2, 10, Multiplicity::ByValue).collect();
+        assert_eq!(solutions, vec![vec![0, 1], vec![3, 4]]);
+        let solutions: Vec<_> = Solutions::new(&numbers, 3, 10, Multiplicity::ByValue).collect();
+        assert_eq!(solutions, vec![vec![0, 1, 3]]);
+    }
+
+    #[test]
+    fn test_outcome() {
+        let unique = Outcome::from_solutions(Solutions::new(&EXAMPLE, 2, 2020, Multiplicity::ByIndex));
+        assert_eq!(unique, Outcome::Unique(vec![0, 3]));
+        let none = Outcome::from_solutions(Solutions::new(&EXAMPLE, 2, 1, Multiplicity::ByIndex));
+        assert_eq!(none.exit_code(), 1);
+        let multiple = Outcome::from_solutions(Solutions::new(&[1, 1, 1], 2, 2, Multiplicity::ByIndex));
+        assert_eq!(multiple.solutions().len(), 3);
+        assert_eq!(multiple.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        assert!(k_sum(&EXAMPLE, 7, 2020).is_empty());
+        assert!(k_sum(&[], 2, 0).is_empty());
+        assert_eq!(k_sum(&EXAMPLE, 0, 0), vec![Vec::<usize>::new()]);
+        assert_eq!(k_sum(&EXAMPLE, 1, 366), vec![vec![2]]);
+        assert_eq!(k_sum(&[-3, 3, 1, 4], 2, 0), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let numbers = [i64::MIN, i64::MAX, -1, 1];
+        assert_eq!(k_sum(&numbers, 2, -1), vec![vec![0, 1]]);
+        assert_eq!(k_sum(&numbers, 2, i64::MIN + 1), vec![vec![0, 3]]);
+        assert!(k_sum(&numbers, 2, i64::MIN).is_empty());
+        assert!(k_sum(&numbers, 2, i64::MAX).is_empty());
+        assert_eq!(k_sum(&numbers, 3, i64::MAX), vec![vec![1, 2, 3]]);
+        assert_eq!(k_sum(&numbers, 3, i64::MIN), vec![vec![0, 2, 3]]);
+    }
+}
@@ -1,29 +1,49 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use day1::input::read_line_file;
+use day1::ksum::{Multiplicity, Outcome, Solutions};
+use std::process::exit;
 
+const TARGET: i64 = 2020;
+
+const USAGE: &str = "Usage: day1 [--distinct] [FILE]";
+
+// Reads input.txt if no file is given, or stdin if the file is "-".
+// Exits with 1 if any search had no solution, or 2 if any had several.
+// With --distinct, solutions using the same values at other positions
+// are only counted once.
 fn main() {
-    let numbers = read_line_file();
-    for i in 0..numbers.len() - 2 {
-        for j in i+1..numbers.len() - 1 {
-            for k in j+1..numbers.len() {
-                let (a, b, c) = (numbers[i], numbers[j], numbers[k]);
-                if a + b + c == 2020 {
-                    println!("{} + {} + {} = 2020", a, b, c);
-                    println!("{} * {} * {} = {}", a, b, c, a * b * c);                    
-                }   
-            }
+    let mut multiplicity = Multiplicity::ByIndex;
+    let mut path = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--distinct" => multiplicity = Multiplicity::ByValue,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => {println!("{}", USAGE); exit(64)}
         }
     }
-}
-
-fn read_line_file() -> Vec<i32> {
-    let mut numbers = Vec::new();
-    let file = File::open("input.txt").unwrap();
-    for readline in io::BufReader::new(file).lines() {
-        if let Ok(line) = readline {
-            let n: i32 = line.trim().parse().unwrap();
-            numbers.push(n)
+    let numbers = read_line_file(path.as_deref().unwrap_or("input.txt")).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(65)
+    });
+    let mut code = 0;
+    for k in 2..=3 {
+        let outcome = Outcome::from_solutions(Solutions::new(&numbers, k, TARGET, multiplicity));
+        match outcome {
+            Outcome::NoSolution => println!("No {} numbers sum to {}", k, TARGET),
+            Outcome::Unique(_) => (),
+            Outcome::Multiple(ref v) => println!("{} solutions for {} numbers", v.len(), k),
+        }
+        for indices in outcome.solutions() {
+            let values: Vec<i64> = indices.iter().map(|&i| numbers[i]).collect();
+            let terms: Vec<String> = values.iter().map(|n| n.to_string()).collect();
+            println!("{} = {}", terms.join(" + "), TARGET);
+            // Three i64 values can overflow even i128
+            let product = values.iter().try_fold(1i128, |p, &n| p.checked_mul(n as i128));
+            match product {
+                Some(product) => println!("{} = {}", terms.join(" * "), product),
+                None => println!("{} = overflow", terms.join(" * ")),
+            }
         }
+        code = code.max(outcome.exit_code());
     }
-    return numbers    
+    exit(code)
 }
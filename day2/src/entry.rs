@@ -0,0 +1,236 @@
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+// One line of the password file, e.g. "3-11 j: tjjj".
+// The policy character is a single grapheme cluster, so it may consist of
+// several scalar values, e.g. "e" followed by a combining accent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub low: usize,
+    pub high: usize,
+    pub chr: &'a str,
+    pub password: &'a str,
+}
+
+// Why a line could not be parsed. Line numbers and columns are 1-based,
+// and columns count characters, not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidNumber { line: usize, column: usize },
+    MissingDelimiter { line: usize, column: usize, expected: &'static str },
+    MissingChar { line: usize, column: usize },
+    InvalidRange { line: usize, column: usize, low: usize, high: usize },
+    EmptyPassword { line: usize, column: usize },
+    TrailingInput { line: usize, column: usize },
+    InvalidUtf8 { line: usize, column: usize },
+}
+
+impl ParseError {
+    pub fn line(&self) -> usize {
+        self.position().0
+    }
+
+    pub fn column(&self) -> usize {
+        self.position().1
+    }
+
+    fn position(&self) -> (usize, usize) {
+        match *self {
+            ParseError::InvalidNumber { line, column }
+            | ParseError::MissingDelimiter { line, column, .. }
+            | ParseError::MissingChar { line, column }
+            | ParseError::InvalidRange { line, column, .. }
+            | ParseError::EmptyPassword { line, column }
+            | ParseError::TrailingInput { line, column }
+            | ParseError::InvalidUtf8 { line, column } => (line, column),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}, column {}: ", self.line(), self.column())?;
+        match self {
+            ParseError::InvalidNumber { .. } => write!(f, "expected a number"),
+            ParseError::MissingDelimiter { expected, .. } => write!(f, "expected \"{}\"", expected),
+            ParseError::MissingChar { .. } => write!(f, "expected the policy character"),
+            ParseError::InvalidRange { low, high, .. } => write!(f, "lower bound {} exceeds upper bound {}", low, high),
+            ParseError::EmptyPassword { .. } => write!(f, "password is empty"),
+            ParseError::TrailingInput { .. } => write!(f, "unexpected input after password"),
+            ParseError::InvalidUtf8 { .. } => write!(f, "invalid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Walks through a line, keeping track of the position for error messages
+struct Cursor<'a> {
+    string: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.string[self.pos..]
+    }
+
+    fn column(&self) -> usize {
+        self.string[..self.pos].chars().count() + 1
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let n = rest[..len].parse::<usize>().map_err(|_| ParseError::InvalidNumber {
+            line: self.line,
+            column: self.column(),
+        })?;
+        self.pos += len;
+        Ok(n)
+    }
+
+    fn expect(&mut self, delimiter: &'static str) -> Result<(), ParseError> {
+        if !self.rest().starts_with(delimiter) {
+            return Err(ParseError::MissingDelimiter {
+                line: self.line,
+                column: self.column(),
+                expected: delimiter,
+            });
+        }
+        self.pos += delimiter.len();
+        Ok(())
+    }
+
+    fn chr(&mut self) -> Result<&'a str, ParseError> {
+        match self.rest().graphemes(true).next() {
+            Some(g) if !g.starts_with(char::is_whitespace) && !g.starts_with(':') => {
+                self.pos += g.len();
+                Ok(g)
+            }
+            _ => Err(ParseError::MissingChar {
+                line: self.line,
+                column: self.column(),
+            }),
+        }
+    }
+}
+
+// Decodes one line of the file. The column of an error is the first
+// character that could not be decoded. `lineno` is only used in errors.
+pub fn decode_line(bytes: &[u8], lineno: usize) -> Result<&str, ParseError> {
+    std::str::from_utf8(bytes).map_err(|e| {
+        let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+        ParseError::InvalidUtf8 {
+            line: lineno,
+            column: valid.chars().count() + 1,
+        }
+    })
+}
+
+// Parses a line like "3-11 j: tjjj". `lineno` is only used in errors.
+// Trailing whitespace is ignored, but the password may not contain whitespace.
+pub fn parse_line<'a>(line: &'a str, lineno: usize) -> Result<Entry<'a>, ParseError> {
+    let mut cursor = Cursor {
+        string: line.trim_end(),
+        pos: 0,
+        line: lineno,
+    };
+    let low = cursor.number()?;
+    cursor.expect("-")?;
+    let highcolumn = cursor.column();
+    let high = cursor.number()?;
+    if low > high {
+        return Err(ParseError::InvalidRange {
+            line: lineno,
+            column: highcolumn,
+            low,
+            high,
+        });
+    }
+    cursor.expect(" ")?;
+    let chr = cursor.chr()?;
+    cursor.expect(": ")?;
+
+    let rest = cursor.rest();
+    let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    if len == 0 {
+        return Err(ParseError::EmptyPassword {
+            line: lineno,
+            column: cursor.column(),
+        });
+    }
+    let password = &rest[..len];
+    cursor.pos += len;
+    if !cursor.rest().is_empty() {
+        return Err(ParseError::TrailingInput {
+            line: lineno,
+            column: cursor.column(),
+        });
+    }
+    Ok(Entry { low, high, chr, password })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_line, parse_line, Entry, ParseError};
+
+    #[test]
+    fn test_parse_line() {
+        let entry = parse_line("3-11 j: tjjj\r\n", 1).unwrap();
+        assert_eq!(entry, Entry { low: 3, high: 11, chr: "j", password: "tjjj" });
+        let entry = parse_line("1-2 e\u{301}: \u{e9}e\u{301}", 1).unwrap();
+        assert_eq!(entry.chr, "e\u{301}");
+        assert_eq!(entry.password, "\u{e9}e\u{301}");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_line("3-x j: abc", 7),
+            Err(ParseError::InvalidNumber { line: 7, column: 3 })
+        );
+        assert_eq!(
+            parse_line("\u{e9}-4 j: abc", 1),
+            Err(ParseError::InvalidNumber { line: 1, column: 1 })
+        );
+        assert_eq!(
+            parse_line("3-4 \u{e9}\u{e9}: abc", 1),
+            Err(ParseError::MissingDelimiter { line: 1, column: 6, expected: ": " })
+        );
+        assert_eq!(
+            parse_line("3-4j: abc", 1),
+            Err(ParseError::MissingDelimiter { line: 1, column: 4, expected: " " })
+        );
+        assert_eq!(
+            parse_line("3-4 : abc", 1),
+            Err(ParseError::MissingChar { line: 1, column: 5 })
+        );
+        assert_eq!(
+            parse_line("5-4 j: abc", 1),
+            Err(ParseError::InvalidRange { line: 1, column: 3, low: 5, high: 4 })
+        );
+        assert_eq!(
+            parse_line("3-4 j: ", 1),
+            Err(ParseError::MissingDelimiter { line: 1, column: 6, expected: ": " })
+        );
+        assert_eq!(
+            parse_line("3-4 j:  abc", 1),
+            Err(ParseError::EmptyPassword { line: 1, column: 8 })
+        );
+        assert_eq!(
+            parse_line("3-4 j: ab c", 1),
+            Err(ParseError::TrailingInput { line: 1, column: 10 })
+        );
+    }
+
+    #[test]
+    fn test_decode_line() {
+        assert_eq!(decode_line("1-2 \u{e9}: ab".as_bytes(), 1), Ok("1-2 \u{e9}: ab"));
+        assert_eq!(
+            decode_line(b"1-2 \xc3\xa9: a\xffb", 4),
+            Err(ParseError::InvalidUtf8 { line: 4, column: 9 })
+        );
+    }
+}
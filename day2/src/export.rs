@@ -0,0 +1,103 @@
+use crate::entry::Entry;
+use crate::policy::PolicySet;
+use json::json_string;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+// Writes one record per entry, with its line number, parsed policy, password
+// and the verdict of every policy in the set. CSV output starts with a header
+// row naming the columns, with one column per policy.
+pub fn write_verdicts<'a, W, I>(out: &mut W, format: Format, policies: &PolicySet, entries: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a (usize, Entry<'a>)>,
+{
+    let names: Vec<String> = policies.policies().iter().map(|p| p.name()).collect();
+    if format == Format::Csv {
+        let mut header = vec!["line", "low", "high", "char", "password"];
+        header.extend(names.iter().map(|name| name.as_str()));
+        let fields: Vec<String> = header.iter().map(|s| csv_field(s)).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    for (lineno, entry) in entries {
+        let verdicts = policies.verdicts(entry);
+        match format {
+            Format::Csv => {
+                let mut fields = vec![
+                    lineno.to_string(),
+                    entry.low.to_string(),
+                    entry.high.to_string(),
+                    csv_field(entry.chr),
+                    csv_field(entry.password),
+                ];
+                fields.extend(verdicts.iter().map(|v| v.to_string()));
+                writeln!(out, "{}", fields.join(","))?;
+            }
+            Format::JsonLines => {
+                let verdicts: Vec<String> = names
+                    .iter()
+                    .zip(verdicts.iter())
+                    .map(|(name, v)| format!("{}:{}", json_string(name), v))
+                    .collect();
+                writeln!(
+                    out,
+                    "{{\"line\":{},\"low\":{},\"high\":{},\"char\":{},\"password\":{},\"verdicts\":{{{}}}}}",
+                    lineno,
+                    entry.low,
+                    entry.high,
+                    json_string(entry.chr),
+                    json_string(entry.password),
+                    verdicts.join(",")
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Quotes the field if it contains a delimiter, quote or line break
+fn csv_field(string: &str) -> String {
+    if string.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", string.replace('"', "\"\""))
+    } else {
+        string.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_verdicts, Format};
+    use crate::entry::parse_line;
+    use crate::policy::PolicySet;
+
+    #[test]
+    fn test_export() {
+        let entries = vec![
+            (1, parse_line("1-3 a: abcde", 1).unwrap()),
+            (3, parse_line("1-3 \": a\"b,", 3).unwrap()),
+        ];
+        let policies = PolicySet::default();
+
+        let mut csv = Vec::new();
+        write_verdicts(&mut csv, Format::Csv, &policies, &entries).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "line,low,high,char,password,count,position\n\
+             1,1,3,a,abcde,true,true\n\
+             3,1,3,\"\"\"\",\"a\"\"b,\",true,false\n"
+        );
+
+        let mut jsonl = Vec::new();
+        write_verdicts(&mut jsonl, Format::JsonLines, &policies, &entries[..1]).unwrap();
+        assert_eq!(
+            String::from_utf8(jsonl).unwrap(),
+            "{\"line\":1,\"low\":1,\"high\":3,\"char\":\"a\",\"password\":\"abcde\",\
+             \"verdicts\":{\"count\":true,\"position\":true}}\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod entry;
+pub mod export;
+pub mod policy;
+pub mod unit;
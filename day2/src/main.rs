@@ -1,82 +1,138 @@
-// 3-11 j: tjjj
-use std::fs::File;
-use std::io::{self, BufRead};
+use day2::entry::{decode_line, parse_line, Entry, ParseError};
+use day2::export::{write_verdicts, Format};
+use day2::policy::{ForbiddenCharsPolicy, LengthPolicy, PolicySet, RegexPolicy};
+use day2::unit::Unit;
 use regex::Regex;
-use std::convert::TryInto;
-
-fn main() {
-    let (re, lines) = setup();
-    println!("{}", second_part(&re, lines))
-}
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::exit;
 
-fn setup() -> (Regex, io::Lines<io::BufReader<File>>) {
-    let re = Regex::new(r"(\d+)\-(\d+) (\w): (\w+)").unwrap();
-    (re, read_lines("input.txt"))
-}
+const USAGE: &str = "Usage: day2 [--unit byte|scalar|grapheme] [--length MIN-MAX] [--forbid CHARS]\n    [--regex PATTERN] [--skip-bad] [--csv OUTFILE] [--jsonl OUTFILE] [FILE]";
 
-fn first_part(re: &Regex, lines: io::Lines<io::BufReader<File>>) -> i32 {
-    let mut n_passed = 0;
-    let (re, lines) = setup();
-    for lineresult in lines {
-        let line = lineresult.unwrap();
-        let (password, chr, low, high) = parse_line(&line, &re);
-        if password_passes_first(password, chr, low, high) {
-            n_passed += 1;
+// Besides the two puzzle policies, more policies can be registered from the
+// command line. Each registered policy gets its own count.
+// By default, the first malformed line aborts the run. With --skip-bad,
+// malformed lines are left out of the counts and reported at the end.
+// With --csv or --jsonl, the verdicts of each line are also written to a file.
+// Positions and lengths are counted in scalar values (Rust chars), unless
+// another unit is given with --unit.
+fn main() {
+    let arglist: Vec<String> = std::env::args().skip(1).collect();
+    let unit = match arglist.iter().position(|a| a == "--unit") {
+        None => Unit::Scalar,
+        Some(i) => arglist.get(i + 1).unwrap_or_else(|| usage()).parse().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            exit(64)
+        }),
+    };
+    let mut policies = PolicySet::puzzle(unit);
+    let mut path = None;
+    let mut skip_bad = false;
+    let mut exports: Vec<(Format, String)> = Vec::new();
+    let mut args = arglist.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--unit" => {
+                args.next();
+            }
+            "--length" | "--forbid" | "--regex" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                let registered = match arg.as_str() {
+                    "--length" => policies.register(Box::new(parse_length(&value, unit))),
+                    "--forbid" => policies.register(Box::new(ForbiddenCharsPolicy {
+                        chars: value.chars().collect(),
+                    })),
+                    _ => {
+                        let re = Regex::new(&value).unwrap_or_else(|e| {
+                            eprintln!("{}", e);
+                            exit(64)
+                        });
+                        policies.register(Box::new(RegexPolicy {
+                            name: format!("regex {}", value),
+                            re,
+                        }))
+                    }
+                };
+                registered.unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    exit(64)
+                })
+            }
+            "--csv" => exports.push((Format::Csv, args.next().unwrap_or_else(|| usage()))),
+            "--jsonl" => exports.push((Format::JsonLines, args.next().unwrap_or_else(|| usage()))),
+            "--skip-bad" => skip_bad = true,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
         }
     }
-    n_passed
-}
+    let path = path.unwrap_or_else(|| "input.txt".to_string());
 
-fn second_part(re: &Regex, lines: io::Lines<io::BufReader<File>>) -> i32 {
-    let mut n_passed = 0;
-    let (re, lines) = setup();
-    for lineresult in lines {
-        let line = lineresult.unwrap();
-        let (password, chr, low, high) = parse_line(&line, &re);
-        if password_passes_second(password, chr, low, high) {
-            n_passed += 1;
+    let contents = std::fs::read(&path).unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {}", path, e);
+        exit(66)
+    });
+    let lines: Vec<&[u8]> = contents.split(|&b| b == b'\n').collect();
+    let (entries, errors) = parse_entries(&lines, skip_bad).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(65)
+    });
+    for (format, outpath) in exports.iter() {
+        let result = File::create(outpath).and_then(|file| {
+            let mut out = io::BufWriter::new(file);
+            write_verdicts(&mut out, *format, &policies, &entries)?;
+            out.flush()
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to write {}: {}", outpath, e);
+            exit(74)
+        }
+    }
+    let counts = policies.count_passing(entries.into_iter().map(|(_lineno, entry)| entry));
+    for (policy, count) in policies.policies().iter().zip(counts) {
+        println!("{}: {}", policy.name(), count)
+    }
+    if !errors.is_empty() {
+        eprintln!("Skipped {} malformed lines:", errors.len());
+        for error in errors.iter() {
+            eprintln!("{}", error)
         }
     }
-    n_passed
-}
-
-fn parse_line<'a>(line: &'a str, re: &Regex) -> (&'a str, char, i32, i32) {
-    let captures = re.captures(&line).unwrap();    
-    let low: i32 = captures.get(1).unwrap().as_str().parse().unwrap();
-    let high: i32 = captures.get(2).unwrap().as_str().parse().unwrap();
-    let chr = captures.get(3).unwrap().as_str().chars().next().unwrap();
-    let password = captures.get(4).unwrap().as_str();
-    return (password, chr, low, high)
 }
 
+// Entries with their line numbers, and errors of the skipped lines
+type ParsedLines<'a> = (Vec<(usize, Entry<'a>)>, Vec<ParseError>);
 
-fn read_lines(path: &str) -> io::Lines<io::BufReader<File>> {
-    let file = File::open(path).unwrap();
-    io::BufReader::new(file).lines()
-}
-
-fn password_passes_first(password: &str, chr: char, low: i32, high: i32) -> bool {
-    let mut n = 0;
-    for i in password.chars() {
-        if i == chr {
-            n += 1;
+// Returns the parsed entries and, if skipping, the errors of the skipped lines.
+// Lines that are not valid UTF-8 are malformed too. Blank lines are ignored.
+fn parse_entries<'a>(lines: &[&'a [u8]], skip_bad: bool) -> Result<ParsedLines<'a>, ParseError> {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let entry = decode_line(line, i + 1).and_then(|line| {
+            if line.trim().is_empty() {
+                return Ok(None);
+            }
+            parse_line(line, i + 1).map(Some)
+        });
+        match entry {
+            Ok(None) => continue,
+            Ok(Some(entry)) => entries.push((i + 1, entry)),
+            Err(e) if skip_bad => errors.push(e),
+            Err(e) => return Err(e),
         }
     }
-    n <= high && low <= n
+    Ok((entries, errors))
 }
 
-fn password_passes_second(password: &str, chr: char, low: i32, high: i32) -> bool {
-    let mut first = false;
-    let mut second = false;
-    for (i, c) in password.chars().enumerate() {
-        if c == chr {
-            let pos: i32 = (i + 1).try_into().unwrap();
-            if pos == low {
-                first = true;
-            } else if pos == high {
-                second = true
-            }
-        }
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(64)
+}
+
+fn parse_length(string: &str, unit: Unit) -> LengthPolicy {
+    let mut fields = string.split('-').map(|s| s.parse::<usize>().unwrap_or_else(|_| usage()));
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(min), Some(max), None) if min <= max => LengthPolicy { min, max, unit },
+        _ => usage(),
     }
-    first ^ second
 }
@@ -0,0 +1,226 @@
+use crate::entry::Entry;
+use crate::unit::Unit;
+use regex::Regex;
+use std::fmt;
+
+// A rule deciding whether the password of an entry is valid. Policies may use
+// the rule written on the line itself, or ignore it and check something else.
+// The name identifies the policy in the output, so it includes the parameters.
+pub trait PasswordPolicy {
+    fn name(&self) -> String;
+    fn passes(&self, entry: &Entry) -> bool;
+}
+
+// The character must occur between low and high times (part 1)
+pub struct CountPolicy {
+    pub unit: Unit,
+}
+
+impl PasswordPolicy for CountPolicy {
+    fn name(&self) -> String {
+        "count".to_string()
+    }
+
+    fn passes(&self, entry: &Entry) -> bool {
+        let n = self.unit.positions(entry.password, entry.chr).len();
+        entry.low <= n && n <= entry.high
+    }
+}
+
+// The character must be at exactly one of the 1-based positions low and high (part 2)
+pub struct PositionPolicy {
+    pub unit: Unit,
+}
+
+impl PasswordPolicy for PositionPolicy {
+    fn name(&self) -> String {
+        "position".to_string()
+    }
+
+    fn passes(&self, entry: &Entry) -> bool {
+        let positions = self.unit.positions(entry.password, entry.chr);
+        let first = positions.contains(&entry.low);
+        let second = entry.high != entry.low && positions.contains(&entry.high);
+        first ^ second
+    }
+}
+
+// The password must have between min and max units, inclusive
+pub struct LengthPolicy {
+    pub min: usize,
+    pub max: usize,
+    pub unit: Unit,
+}
+
+impl PasswordPolicy for LengthPolicy {
+    fn name(&self) -> String {
+        format!("length {}-{} {}", self.min, self.max, self.unit.plural())
+    }
+
+    fn passes(&self, entry: &Entry) -> bool {
+        let n = self.unit.length(entry.password);
+        self.min <= n && n <= self.max
+    }
+}
+
+// The password must not contain any of the given characters
+pub struct ForbiddenCharsPolicy {
+    pub chars: Vec<char>,
+}
+
+impl PasswordPolicy for ForbiddenCharsPolicy {
+    fn name(&self) -> String {
+        format!("forbidden {}", self.chars.iter().collect::<String>())
+    }
+
+    fn passes(&self, entry: &Entry) -> bool {
+        !entry.password.chars().any(|c| self.chars.contains(&c))
+    }
+}
+
+// The password must match the regex somewhere. Anchor it with ^ and $
+// to match the whole password.
+pub struct RegexPolicy {
+    pub name: String,
+    pub re: Regex,
+}
+
+impl PasswordPolicy for RegexPolicy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn passes(&self, entry: &Entry) -> bool {
+        self.re.is_match(entry.password)
+    }
+}
+
+// A policy was registered under a name that is already taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePolicy {
+    pub name: String,
+}
+
+impl fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Policy \"{}\" is already registered", self.name)
+    }
+}
+
+impl std::error::Error for DuplicatePolicy {}
+
+// The policies to check each entry against, in the order they were registered.
+// Names are unique, so they can be used as keys and column headers.
+pub struct PolicySet {
+    policies: Vec<Box<dyn PasswordPolicy>>,
+}
+
+impl Default for PolicySet {
+    fn default() -> Self {
+        PolicySet::puzzle(Unit::Scalar)
+    }
+}
+
+impl PolicySet {
+    pub fn new() -> Self {
+        PolicySet { policies: Vec::new() }
+    }
+
+    // The two policies of the puzzle
+    pub fn puzzle(unit: Unit) -> Self {
+        PolicySet {
+            policies: vec![Box::new(CountPolicy { unit }), Box::new(PositionPolicy { unit })],
+        }
+    }
+
+    pub fn register(&mut self, policy: Box<dyn PasswordPolicy>) -> Result<(), DuplicatePolicy> {
+        let name = policy.name();
+        if self.policies.iter().any(|p| p.name() == name) {
+            return Err(DuplicatePolicy { name });
+        }
+        self.policies.push(policy);
+        Ok(())
+    }
+
+    pub fn policies(&self) -> &[Box<dyn PasswordPolicy>] {
+        &self.policies
+    }
+
+    // Verdict of each policy, in registration order
+    pub fn verdicts(&self, entry: &Entry) -> Vec<bool> {
+        self.policies.iter().map(|p| p.passes(entry)).collect()
+    }
+
+    // Checks every entry once against all policies, and returns how many
+    // entries passed each policy, in registration order.
+    pub fn count_passing<'a, I: IntoIterator<Item = Entry<'a>>>(&self, entries: I) -> Vec<usize> {
+        let mut counts = vec![0; self.policies.len()];
+        for entry in entries {
+            for (count, policy) in counts.iter_mut().zip(self.policies.iter()) {
+                *count += policy.passes(&entry) as usize;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DuplicatePolicy, ForbiddenCharsPolicy, LengthPolicy, PolicySet, RegexPolicy};
+    use crate::entry::parse_line;
+    use crate::unit::Unit;
+    use regex::Regex;
+
+    const EXAMPLE: [&str; 3] = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+
+    #[test]
+    fn test_default_policies() {
+        let set = PolicySet::default();
+        let counts = set.count_passing(EXAMPLE.iter().map(|line| parse_line(line, 1).unwrap()));
+        assert_eq!(counts, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_registered_policies() {
+        let mut set = PolicySet::new();
+        set.register(Box::new(LengthPolicy { min: 6, max: 9, unit: Unit::Scalar })).unwrap();
+        set.register(Box::new(ForbiddenCharsPolicy { chars: vec!['a', 'g'] })).unwrap();
+        set.register(Box::new(RegexPolicy {
+            name: "repeated".to_string(),
+            re: Regex::new(r"^(c+)$").unwrap(),
+        }))
+        .unwrap();
+        let counts = set.count_passing(EXAMPLE.iter().map(|line| parse_line(line, 1).unwrap()));
+        assert_eq!(counts, vec![1, 1, 1]);
+        let names: Vec<String> = set.policies().iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["length 6-9 scalars", "forbidden ag", "repeated"]);
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let mut set = PolicySet::default();
+        set.register(Box::new(LengthPolicy { min: 1, max: 3, unit: Unit::Scalar })).unwrap();
+        set.register(Box::new(LengthPolicy { min: 1, max: 4, unit: Unit::Scalar })).unwrap();
+        set.register(Box::new(LengthPolicy { min: 1, max: 3, unit: Unit::Byte })).unwrap();
+        assert_eq!(
+            set.register(Box::new(LengthPolicy { min: 1, max: 3, unit: Unit::Scalar })),
+            Err(DuplicatePolicy { name: "length 1-3 scalars".to_string() })
+        );
+        assert_eq!(set.policies().len(), 5);
+    }
+
+    #[test]
+    fn test_non_ascii_policies() {
+        let entries = [
+            parse_line("1-2 \u{e9}: \u{e9}t\u{e9}", 1).unwrap(),
+            parse_line("2-3 e: e\u{301}e", 2).unwrap(),
+        ];
+        let verdicts = |unit| {
+            let set = PolicySet::puzzle(unit);
+            entries.iter().map(|e| set.verdicts(e)).collect::<Vec<_>>()
+        };
+        assert_eq!(verdicts(Unit::Byte), vec![vec![true, true], vec![true, false]]);
+        assert_eq!(verdicts(Unit::Scalar), vec![vec![true, true], vec![true, true]]);
+        assert_eq!(verdicts(Unit::Grapheme), vec![vec![true, true], vec![false, true]]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+// What a position or a length in a password is counted in. For ASCII
+// passwords these all agree. "é" is 2 bytes and 1 scalar value when
+// precomposed (U+00E9), but 3 bytes and 2 scalar values when written as "e"
+// followed by a combining accent (U+0301). It is 1 grapheme cluster either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Byte,
+    Scalar,
+    Grapheme,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "byte" => Ok(Unit::Byte),
+            "scalar" => Ok(Unit::Scalar),
+            "grapheme" => Ok(Unit::Grapheme),
+            _ => Err(format!("Unknown unit \"{}\", expected byte, scalar or grapheme", string)),
+        }
+    }
+}
+
+impl Unit {
+    // Name of the unit in lengths, e.g. "3 bytes"
+    pub fn plural(self) -> &'static str {
+        match self {
+            Unit::Byte => "bytes",
+            Unit::Scalar => "scalars",
+            Unit::Grapheme => "graphemes",
+        }
+    }
+
+    // Byte ranges of the units of the string, in order
+    fn spans(self, string: &str) -> Vec<(usize, usize)> {
+        match self {
+            Unit::Byte => (0..string.len()).map(|i| (i, i + 1)).collect(),
+            Unit::Scalar => string.char_indices().map(|(i, c)| (i, i + c.len_utf8())).collect(),
+            Unit::Grapheme => string
+                .grapheme_indices(true)
+                .map(|(i, g)| (i, i + g.len()))
+                .collect(),
+        }
+    }
+
+    pub fn length(self, string: &str) -> usize {
+        match self {
+            Unit::Byte => string.len(),
+            Unit::Scalar => string.chars().count(),
+            Unit::Grapheme => string.graphemes(true).count(),
+        }
+    }
+
+    // The 1-based positions at which `pattern` occurs in `string`.
+    // With bytes and scalar values, the pattern matches at a position if the
+    // string continues with its bytes from there, so "e" matches the start of
+    // a decomposed "é". With graphemes, the whole cluster must be equal.
+    pub fn positions(self, string: &str, pattern: &str) -> Vec<usize> {
+        let bytes = string.as_bytes();
+        self.spans(string)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_i, (start, end))| match self {
+                Unit::Grapheme => &bytes[start..end] == pattern.as_bytes(),
+                _ => bytes[start..].starts_with(pattern.as_bytes()),
+            })
+            .map(|(i, _span)| i + 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Unit;
+
+    const UNITS: [Unit; 3] = [Unit::Byte, Unit::Scalar, Unit::Grapheme];
+
+    #[test]
+    fn test_ascii() {
+        for &unit in UNITS.iter() {
+            assert_eq!(unit.length("abcab"), 5);
+            assert_eq!(unit.positions("abcab", "b"), vec![2, 5]);
+        }
+    }
+
+    #[test]
+    fn test_precomposed() {
+        let password = "\u{e9}t\u{e9}";
+        assert_eq!(Unit::Byte.length(password), 5);
+        assert_eq!(Unit::Scalar.length(password), 3);
+        assert_eq!(Unit::Grapheme.length(password), 3);
+        assert_eq!(Unit::Byte.positions(password, "\u{e9}"), vec![1, 4]);
+        assert_eq!(Unit::Scalar.positions(password, "\u{e9}"), vec![1, 3]);
+        assert_eq!(Unit::Grapheme.positions(password, "\u{e9}"), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_combining() {
+        let password = "e\u{301}e";
+        assert_eq!(Unit::Byte.length(password), 4);
+        assert_eq!(Unit::Scalar.length(password), 3);
+        assert_eq!(Unit::Grapheme.length(password), 2);
+        assert_eq!(Unit::Byte.positions(password, "e"), vec![1, 4]);
+        assert_eq!(Unit::Scalar.positions(password, "e"), vec![1, 3]);
+        assert_eq!(Unit::Grapheme.positions(password, "e"), vec![2]);
+        assert_eq!(Unit::Grapheme.positions(password, "e\u{301}"), vec![1]);
+    }
+
+    #[test]
+    fn test_emoji() {
+        // Family emoji: four people joined by zero width joiners
+        let password = "a\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}a";
+        assert_eq!(Unit::Scalar.length(password), 9);
+        assert_eq!(Unit::Grapheme.length(password), 3);
+        assert_eq!(Unit::Scalar.positions(password, "a"), vec![1, 9]);
+        assert_eq!(Unit::Grapheme.positions(password, "a"), vec![1, 3]);
+    }
+}
@@ -0,0 +1,42 @@
+pub mod render;
+pub mod search;
+pub mod stream;
+
+use grid::{Grid, GridError};
+use stream::StreamError;
+
+// The map repeats to the right, so it must wrap horizontally.
+// Panics if the slope goes zero rows down, as it would never reach the bottom.
+pub fn traverse(map: &Grid<bool>, right: usize, down: usize) -> usize {
+    assert!(down > 0, "Slope must go at least one row down");
+    let mut n = 0;
+    let mut xpos = 0;
+    let mut ypos = 0;
+
+    while let Some(&tree) = map.get(ypos as isize, xpos as isize) {
+        n += tree as usize;
+        xpos += right;
+        ypos += down
+    }
+    n
+}
+
+// Fails like the streaming reader if the file cannot be read or is not a map
+pub fn parse_map(path: &str) -> Result<Grid<bool>, StreamError> {
+    let string = std::fs::read_to_string(path)?;
+    Ok(parse_map_str(&string)?)
+}
+
+// The map repeats to the right, so it wraps horizontally
+pub fn parse_map_str(string: &str) -> Result<Grid<bool>, GridError> {
+    Ok(Grid::parse(string, parse_cell)?.with_wrapping(true, false))
+}
+
+// Trees are #, open squares are .
+pub(crate) fn parse_cell(chr: char) -> Option<bool> {
+    match chr {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None
+    }
+}
@@ -1,71 +1,105 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use day3::{parse_map, traverse};
+use day3::render::{render_ascii, render_svg};
+use day3::search::{primitive_slopes, rank, SlopeCount};
+use day3::stream::traverse_file;
+use std::ops::RangeInclusive;
+use std::process::exit;
 
+const USAGE: &str = "Usage: day3 [--search RIGHT_MIN-RIGHT_MAX DOWN_MIN-DOWN_MAX] [--stream]\n    [--render RIGHT DOWN] [--svg RIGHT DOWN OUTFILE] [FILE]";
+
+const PUZZLE_SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+// With --search, every slope in the ranges is tried instead of the puzzle's
+// five, and the slopes are listed by number of trees hit.
+// With --render or --svg, the path of a single slope is drawn on the map.
+// With --stream, the map is read one row at a time instead of loaded whole,
+// for maps too large to fit in memory. This does not work with rendering.
+// Slopes must go at least one row down.
 fn main() {
-    let rows = parse_rows();
-    let mut prod = 1;
-    for (right, down) in vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
-        let trees = traverse(&rows, right, down);
-        println!("{}", trees);
-        prod *= trees;
+    let mut path = None;
+    let mut search = None;
+    let mut stream = false;
+    let mut render = None;
+    let mut svgpath = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--search" => {
+                let rights = parse_range(&args.next().unwrap_or_else(|| usage()));
+                let downs = parse_range(&args.next().unwrap_or_else(|| usage()));
+                search = Some((rights, downs))
+            }
+            "--render" | "--svg" => {
+                let right = parse_number(&args.next().unwrap_or_else(|| usage()));
+                let down = parse_number(&args.next().unwrap_or_else(|| usage()));
+                if down == 0 {
+                    usage()
+                }
+                render = Some((right, down));
+                if arg == "--svg" {
+                    svgpath = Some(args.next().unwrap_or_else(|| usage()))
+                }
+            }
+            "--stream" => stream = true,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
     }
-    println!("{}", prod)
-}
-
-fn traverse(vecs: &Vec<Vec<bool>>, right: usize, down: usize) -> usize {
-    if vecs.len() == 0 {
-        return 0
+    let path = path.unwrap_or_else(|| "input.txt".to_string());
+    if let Some((right, down)) = render {
+        let map = parse_map(&path).unwrap_or_else(|e| {eprintln!("{}", e); exit(65)});
+        match svgpath {
+            None => print!("{}", render_ascii(&map, right, down)),
+            Some(outpath) => std::fs::write(&outpath, render_svg(&map, right, down))
+                .unwrap_or_else(|e| {eprintln!("Failed to write {}: {}", outpath, e); exit(74)}),
+        }
+        return
     }
-    
-    let mut n = 0;
-    let mut xpos = 0;
-    let mut ypos = 0;
-    let width = vecs[0].len();
 
-    while ypos < vecs.len() {
-        let p: usize = vecs[ypos][xpos].into();
-        n += p;
-        xpos = (xpos + right) % width;
-        ypos += down
-    }
-    return n
-}
+    let slopes = match search.clone() {
+        Some((rights, downs)) => primitive_slopes(rights, downs),
+        None => PUZZLE_SLOPES.to_vec(),
+    };
+    let counts: Vec<usize> = if stream {
+        traverse_file(&path, &slopes).unwrap_or_else(|e| {eprintln!("{}", e); exit(65)})
+    } else {
+        let map = parse_map(&path).unwrap_or_else(|e| {eprintln!("{}", e); exit(65)});
+        slopes.iter().map(|&(right, down)| traverse(&map, right, down)).collect()
+    };
 
-fn parse_rows() -> Vec<Vec<bool>> {
-    let mut result = Vec::new();
-    let file = File::open("input.txt").unwrap();
-    let lines = io::BufReader::new(file).lines();
-    for lineresult in lines {
-        let row = parse_row(&lineresult.unwrap());
-        result.push(row)
+    if search.is_some() {
+        let ranked = rank(slopes.iter().zip(counts).map(|(&(right, down), trees)| {
+            SlopeCount { right, down, trees }
+        }).collect());
+        for count in ranked.iter() {
+            println!("right {}, down {}: {}", count.right, count.down, count.trees);
+        }
+        if let (Some(min), Some(max)) = (ranked.first(), ranked.last()) {
+            println!("Fewest trees: right {}, down {}: {}", min.right, min.down, min.trees);
+            println!("Most trees: right {}, down {}: {}", max.right, max.down, max.trees);
+        }
+        return
     }
 
-    // Validate all are same length
-    if !all_same_length(&result) {
-        panic!("Not all vectors are same length")
+    for trees in counts.iter() {
+        println!("{}", trees);
     }
+    println!("{}", counts.iter().product::<usize>())
+}
 
-    return result
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(64)
 }
 
-fn all_same_length<T>(vecs: &Vec<Vec<T>>) -> bool {
-    if vecs.len() == 0 {
-        return true
-    }
-    let mut iter = vecs.into_iter();
-    let firstlen = iter.next().unwrap().len();
-    return iter.all(|x| x.len() == firstlen)
+fn parse_number(string: &str) -> usize {
+    string.parse::<usize>().unwrap_or_else(|_| usage())
 }
 
-fn parse_row(row: &str) -> Vec<bool> {
-    let mut result = Vec::new();
-    for chr in row.chars() {
-        let bl = match chr {
-            '#' => true,
-            '.' => false,
-            _ => panic!("Unknown char: {}", chr)
-        };
-        result.push(bl)
+fn parse_range(string: &str) -> RangeInclusive<usize> {
+    let mut fields = string.split('-').map(|s| s.parse::<usize>().unwrap_or_else(|_| usage()));
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(low), Some(high), None) => low..=high,
+        _ => usage()
     }
-    return result
 }
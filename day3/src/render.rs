@@ -0,0 +1,167 @@
+use grid::Grid;
+use std::fmt::Write;
+
+// Pixels per map square in SVG output
+const SVG_CELL: usize = 10;
+
+// The squares visited when going along the slope, as (row, column), where
+// the column keeps counting past the right edge of the map instead of wrapping.
+// Like `traverse`, this includes the starting square, and panics if the slope
+// goes zero rows down.
+pub fn path_squares(map: &Grid<bool>, right: usize, down: usize) -> Vec<(usize, usize)> {
+    assert!(down > 0, "Slope must go at least one row down");
+    if map.is_empty() {
+        return Vec::new();
+    }
+    (0..map.nrows())
+        .step_by(down)
+        .enumerate()
+        .map(|(step, row)| (row, step * right))
+        .collect()
+}
+
+// Number of times the map must be repeated to the right to fit the path
+fn tiles_needed(map: &Grid<bool>, path: &[(usize, usize)]) -> usize {
+    match path.last() {
+        None => 1,
+        Some(&(_row, col)) => col / map.ncols() + 1,
+    }
+}
+
+// Draws the map, repeated to the right as far as the path goes, like in the
+// puzzle text: trees are #, open squares are ., and squares on the path are
+// X if they hold a tree and O otherwise.
+pub fn render_ascii(map: &Grid<bool>, right: usize, down: usize) -> String {
+    let path = path_squares(map, right, down);
+    let tiles = tiles_needed(map, &path);
+    let mut result = String::new();
+    let mut onpath = path.iter().peekable();
+    for (rowno, row) in map.rows().enumerate() {
+        let pathcol = match onpath.peek() {
+            Some(&&(r, c)) if r == rowno => {
+                onpath.next();
+                Some(c)
+            }
+            _ => None,
+        };
+        for col in 0..(tiles * map.ncols()) {
+            let tree = row[col % map.ncols()];
+            let chr = match (pathcol == Some(col), tree) {
+                (true, true) => 'X',
+                (true, false) => 'O',
+                (false, true) => '#',
+                (false, false) => '.',
+            };
+            result.push(chr);
+        }
+        result.push('\n');
+    }
+    result
+}
+
+// Same picture as `render_ascii`, as an SVG image. Trees are green squares,
+// and the path is a line through the visited squares, with a red dot on every
+// tree hit and a white dot on every open square.
+pub fn render_svg(map: &Grid<bool>, right: usize, down: usize) -> String {
+    let path = path_squares(map, right, down);
+    let tiles = tiles_needed(map, &path);
+    let (width, height) = (tiles * map.ncols() * SVG_CELL, map.nrows() * SVG_CELL);
+    let center = |n: usize| n * SVG_CELL + SVG_CELL / 2;
+
+    // Writing to a String cannot fail, so the results are ignored
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    );
+    let _ = writeln!(svg, "<rect width=\"{}\" height=\"{}\" fill=\"#f4f0e6\"/>", width, height);
+    for (rowno, row) in map.rows().enumerate() {
+        for col in 0..(tiles * map.ncols()) {
+            if row[col % map.ncols()] {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#2e7d32\"/>",
+                    col * SVG_CELL,
+                    rowno * SVG_CELL,
+                    SVG_CELL,
+                    SVG_CELL
+                );
+            }
+        }
+    }
+    // Vertical lines where the map repeats
+    for tile in 1..tiles {
+        let x = tile * map.ncols() * SVG_CELL;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"0\" x2=\"{}\" y2=\"{}\" stroke=\"#999\" stroke-dasharray=\"4\"/>",
+            x, x, height
+        );
+    }
+    if !path.is_empty() {
+        let points: Vec<String> = path
+            .iter()
+            .map(|&(row, col)| format!("{},{}", center(col), center(row)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"#1565c0\" stroke-width=\"2\"/>",
+            points.join(" ")
+        );
+    }
+    for &(row, col) in path.iter() {
+        let fill = if map[(row, col % map.ncols())] { "#c62828" } else { "#ffffff" };
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"#1565c0\"/>",
+            center(col),
+            center(row),
+            SVG_CELL / 3,
+            fill
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{path_squares, render_ascii, render_svg};
+    use crate::parse_map_str;
+    use grid::Grid;
+
+    fn map() -> Grid<bool> {
+        parse_map_str("..#\n#..\n.#.\n##.").unwrap()
+    }
+
+    #[test]
+    fn test_path_squares() {
+        assert_eq!(path_squares(&map(), 2, 1), vec![(0, 0), (1, 2), (2, 4), (3, 6)]);
+        assert_eq!(path_squares(&map(), 1, 2), vec![(0, 0), (2, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one row down")]
+    fn test_path_squares_flat_slope() {
+        path_squares(&map(), 1, 0);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        assert_eq!(
+            render_ascii(&map(), 2, 1),
+            "O.#..#..#\n#.O#..#..\n.#..X..#.\n##.##.X#.\n"
+        );
+        assert_eq!(render_ascii(&map(), 1, 2), "O.#\n#..\n.X.\n##.\n");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg(&map(), 2, 1);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"90\" height=\"40\""));
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert_eq!(svg.matches("#c62828").count(), 2);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
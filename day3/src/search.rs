@@ -0,0 +1,80 @@
+use crate::traverse;
+use grid::Grid;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlopeCount {
+    pub right: usize,
+    pub down: usize,
+    pub trees: usize,
+}
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// All slopes in the ranges, except those that are a multiple of another slope
+// in the ranges. E.g. (6, 2) steps along the same line as (3, 1), but only
+// hits every other point of it, so it is left out when (3, 1) is included.
+// Slopes going zero rows down are always left out, as they never reach the bottom.
+pub fn primitive_slopes(rights: RangeInclusive<usize>, downs: RangeInclusive<usize>) -> Vec<(usize, usize)> {
+    let mut slopes = Vec::new();
+    for right in rights.clone() {
+        for down in downs.clone() {
+            if down == 0 {
+                continue;
+            }
+            let divisor = gcd(right, down);
+            if divisor > 1 && rights.contains(&(right / divisor)) && downs.contains(&(down / divisor)) {
+                continue;
+            }
+            slopes.push((right, down));
+        }
+    }
+    slopes
+}
+
+// Traverses the map along every slope, and returns the counts ranked
+pub fn rank_slopes<I: IntoIterator<Item = (usize, usize)>>(map: &Grid<bool>, slopes: I) -> Vec<SlopeCount> {
+    rank(slopes
+        .into_iter()
+        .map(|(right, down)| SlopeCount { right, down, trees: traverse(map, right, down) })
+        .collect())
+}
+
+// Sorts the counts by number of trees, fewest first. Ties are ordered by slope.
+pub fn rank(mut counts: Vec<SlopeCount>) -> Vec<SlopeCount> {
+    counts.sort_by_key(|c| (c.trees, c.right, c.down));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gcd, primitive_slopes, rank_slopes, SlopeCount};
+    use crate::parse_map_str;
+
+    #[test]
+    fn test_primitive_slopes() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 3), 3);
+        assert_eq!(
+            primitive_slopes(0..=2, 0..=2),
+            vec![(0, 1), (1, 1), (1, 2), (2, 1)]
+        );
+        // (2, 2) stays when (1, 1) is outside the ranges
+        assert_eq!(primitive_slopes(2..=2, 1..=2), vec![(2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_rank_slopes() {
+        let map = parse_map_str("..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n\
+            .#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n\
+            #...##....#\n.#..#...#.#")
+        .unwrap();
+        let ranked = rank_slopes(&map, vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]);
+        let trees: Vec<usize> = ranked.iter().map(|c| c.trees).collect();
+        assert_eq!(trees, vec![2, 2, 3, 4, 7]);
+        assert_eq!(ranked[0], SlopeCount { right: 1, down: 1, trees: 2 });
+        assert_eq!(ranked[4], SlopeCount { right: 3, down: 1, trees: 7 });
+    }
+}
@@ -0,0 +1,175 @@
+use crate::parse_cell;
+use grid::GridError;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+// A row of the map with one bit per square, set for trees
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitRow {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitRow {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Replaces the contents with the parsed line, reusing the allocation.
+    // `row` is only used in errors.
+    pub fn parse_into(&mut self, line: &str, row: usize) -> Result<(), GridError> {
+        self.words.clear();
+        self.len = 0;
+        for (col, chr) in line.chars().enumerate() {
+            let tree = parse_cell(chr).ok_or(GridError::UnknownChar { row, col, chr })?;
+            if col % 64 == 0 {
+                self.words.push(0);
+            }
+            self.words[col / 64] |= (tree as u64) << (col % 64);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, col: usize) -> bool {
+        assert!(col < self.len, "Column {} outside row of length {}", col, self.len);
+        (self.words[col / 64] >> (col % 64)) & 1 == 1
+    }
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Map(GridError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "Failed to read map: {}", e),
+            StreamError::Map(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl From<GridError> for StreamError {
+    fn from(e: GridError) -> Self {
+        StreamError::Map(e)
+    }
+}
+
+// Counts the trees hit along each slope, like `traverse`, but reads the map
+// one row at a time, so only a single row is held in memory no matter how
+// large the map is. All slopes are evaluated in the same pass.
+// Panics if a slope goes zero rows down, like `traverse`.
+pub fn traverse_stream<R: BufRead>(mut reader: R, slopes: &[(usize, usize)]) -> Result<Vec<usize>, StreamError> {
+    assert!(slopes.iter().all(|&(_right, down)| down > 0), "Slope must go at least one row down");
+    let mut trees = vec![0; slopes.len()];
+    // Column of each slope, kept within the width of the map
+    let mut cols = vec![0; slopes.len()];
+    let mut line = String::new();
+    let mut row = BitRow::new();
+    let mut width = None;
+    let mut rowno = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(trees);
+        }
+        row.parse_into(line.trim_end_matches(&['\n', '\r'][..]), rowno)?;
+        let width = *width.get_or_insert(row.len());
+        if row.len() != width {
+            return Err(GridError::UnequalWidth { row: rowno, expected: width, found: row.len() }.into());
+        }
+        if width > 0 {
+            for (i, &(right, down)) in slopes.iter().enumerate() {
+                if rowno % down == 0 {
+                    trees[i] += row.get(cols[i]) as usize;
+                    cols[i] = (cols[i] + right % width) % width;
+                }
+            }
+        }
+        rowno += 1;
+    }
+}
+
+pub fn traverse_file(path: &str, slopes: &[(usize, usize)]) -> Result<Vec<usize>, StreamError> {
+    traverse_stream(BufReader::new(File::open(path)?), slopes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{traverse_stream, BitRow, StreamError};
+    use crate::{parse_map_str, traverse};
+    use grid::{Grid, GridError};
+
+    const EXAMPLE: &str = "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n\
+        .#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n\
+        #...##....#\n.#..#...#.#\n";
+
+    #[test]
+    fn test_bitrow() {
+        let mut row = BitRow::new();
+        let line: String = (0..130).map(|i| if i % 3 == 0 { '#' } else { '.' }).collect();
+        row.parse_into(&line, 0).unwrap();
+        assert_eq!(row.len(), 130);
+        assert!((0..130).all(|i| row.get(i) == (i % 3 == 0)));
+        row.parse_into("#.", 1).unwrap();
+        assert_eq!(row.len(), 2);
+        assert!(row.get(0) && !row.get(1));
+    }
+
+    #[test]
+    fn test_stream_matches_grid() {
+        let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2), (0, 3), (13, 1), (2, 5)];
+        let map = parse_map_str(EXAMPLE).unwrap();
+        let expected: Vec<usize> = slopes.iter().map(|&(r, d)| traverse(&map, r, d)).collect();
+        let input = EXAMPLE.replace('\n', "\r\n");
+        assert_eq!(traverse_stream(input.as_bytes(), &slopes).unwrap(), expected);
+        assert_eq!(&expected[..5], &[2, 7, 3, 4, 2]);
+    }
+
+    // Both traversals reject a slope that never goes down, instead of
+    // counting it differently
+    #[test]
+    #[should_panic(expected = "at least one row down")]
+    fn test_stream_rejects_flat_slope() {
+        let _ = traverse_stream(EXAMPLE.as_bytes(), &[(1, 1), (3, 0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one row down")]
+    fn test_traverse_rejects_flat_slope() {
+        let map = Grid::new(2, 2, false).with_wrapping(true, false);
+        traverse(&map, 3, 0);
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        match traverse_stream("..#\n#.\n".as_bytes(), &[(1, 1)]) {
+            Err(StreamError::Map(GridError::UnequalWidth { row: 1, expected: 3, found: 2 })) => (),
+            other => panic!("Expected UnequalWidth, got {:?}", other),
+        }
+        match traverse_stream("..#\n#.x\n".as_bytes(), &[(1, 1)]) {
+            Err(StreamError::Map(GridError::UnknownChar { row: 1, col: 2, chr: 'x' })) => (),
+            other => panic!("Expected UnknownChar, got {:?}", other),
+        }
+    }
+}
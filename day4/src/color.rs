@@ -0,0 +1,393 @@
+use crate::error::ErrorKind;
+use crate::malformed;
+use std::fmt;
+use std::str::FromStr;
+
+// Eye colors are one of the seven ecl codes, hair colors are RGB.
+// Outside of passports, colors may also be written as #rgb shorthand, in
+// uppercase, or as CSS color names, see `FromStr`. The eye colors that are
+// also CSS colors are named variants however they are written, so "blue" is
+// `Blue` like "blu". Only hex values are always `RGB`, so `Blue` and #0000ff
+// differ, but have the same `rgb()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Brown,
+    Hazel,
+    Green,
+    Grey,
+    Blue,
+    Ambiguous,
+    Other,
+    RGB(u8, u8, u8)
+}
+
+const HCL_EXPECTED: &str = "# followed by 6 lowercase hex digits";
+
+// The eye colors that are also CSS colors, by their CSS names
+const EYE_CSS_NAMES: [(&str, Color); 5] = [
+    ("blue", Color::Blue),
+    ("brown", Color::Brown),
+    ("gray", Color::Grey),
+    ("green", Color::Green),
+    ("grey", Color::Grey),
+];
+
+// The CSS named colors, sorted by name. Some colors have two names, like
+// aqua and cyan, or gray and grey.
+pub const CSS_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+fn unpack(rgb: u32) -> (u8, u8, u8) {
+    ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+// CSS names ignore case, spaces, dashes and underscores
+fn css_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl Color {
+    pub(crate) fn from_ecl(str: &str) -> Result<Color, ErrorKind> {
+        let color = match str {
+            "brn" => Color::Brown,
+            "hzl" => Color::Hazel,
+            "grn" => Color::Green,
+            "gry" => Color::Grey,
+            "blu" => Color::Blue,
+            "amb" => Color::Ambiguous,
+            "oth" => Color::Other,
+            _ => return Err(malformed("ecl", str, "one of amb, blu, brn, gry, grn, hzl, oth", None))
+        };
+
+        Ok(color)
+    }
+
+    // Unlike `from_hex`, only the full lowercase form is allowed
+    pub(crate) fn from_hcl(str: &str) -> Result<Color, ErrorKind> {
+        let lowercase = |c: char| c.is_ascii_digit() || ('a'..='f').contains(&c);
+        match str.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 && hex.chars().all(lowercase) => {
+                Color::from_hex(str).ok_or_else(|| malformed("hcl", str, HCL_EXPECTED, None))
+            }
+            _ => Err(malformed("hcl", str, HCL_EXPECTED, None)),
+        }
+    }
+
+    // Parses #rrggbb or the #rgb shorthand, in either case.
+    // #rgb means #rrggbb with each digit doubled, so #fa0 is #ffaa00.
+    pub fn from_hex(string: &str) -> Option<Color> {
+        let hex = string.strip_prefix('#')?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None
+        }
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap();
+        match hex.len() {
+            3 => Some(Color::RGB(digit(0) * 17, digit(1) * 17, digit(2) * 17)),
+            6 => {
+                let (r, g, b) = unpack(u32::from_str_radix(hex, 16).unwrap());
+                Some(Color::RGB(r, g, b))
+            }
+            _ => None
+        }
+    }
+
+    // Looks up a CSS color name. Case, spaces, dashes and underscores are
+    // ignored, so "Light Blue" is lightblue. Always gives an RGB color, see
+    // `from_name` for eye colors.
+    pub fn from_css_name(name: &str) -> Option<Color> {
+        let name = css_key(name);
+        let index = CSS_COLORS.binary_search_by(|(n, _)| (*n).cmp(name.as_str())).ok()?;
+        let (r, g, b) = unpack(CSS_COLORS[index].1);
+        Some(Color::RGB(r, g, b))
+    }
+
+    // Like `from_css_name`, but the names of eye colors give those, so
+    // "Blue" is `Blue` and "gray" is `Grey`
+    pub fn from_name(name: &str) -> Option<Color> {
+        let key = css_key(name);
+        EYE_CSS_NAMES.iter()
+            .find(|(n, _)| *n == key)
+            .map(|(_, color)| *color)
+            .or_else(|| Color::from_css_name(name))
+    }
+
+    // The RGB value of the color. Eye colors that are also CSS colors have
+    // that value, the others (hazel, ambiguous and other) have none.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let name = match self {
+            Color::RGB(r, g, b) => return Some((*r, *g, *b)),
+            Color::Brown => "brown",
+            Color::Green => "green",
+            Color::Grey => "grey",
+            Color::Blue => "blue",
+            Color::Hazel | Color::Ambiguous | Color::Other => return None,
+        };
+        Color::from_css_name(name).and_then(|c| c.rgb())
+    }
+
+    // The first CSS name with exactly this RGB value, if any
+    pub fn css_name(&self) -> Option<&'static str> {
+        let (r, g, b) = self.rgb()?;
+        CSS_COLORS.iter().find(|(_, rgb)| unpack(*rgb) == (r, g, b)).map(|(name, _)| *name)
+    }
+
+    // The CSS color closest to this one, by euclidean distance in RGB space.
+    // Of equally close colors, the first by name wins.
+    pub fn nearest_css_name(&self) -> Option<&'static str> {
+        let (r, g, b) = self.rgb()?;
+        let distance = |rgb: u32| {
+            let (r2, g2, b2) = unpack(rgb);
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(r, r2) + d(g, g2) + d(b, b2)
+        };
+        CSS_COLORS.iter().min_by_key(|(_, rgb)| distance(*rgb)).map(|(name, _)| *name)
+    }
+
+    // The value as written in a batch file: ecl codes for named colors,
+    // and #rrggbb like hcl for RGB colors
+    pub fn to_field_value(&self) -> String {
+        let code = match self {
+            Color::Brown => "brn",
+            Color::Hazel => "hzl",
+            Color::Green => "grn",
+            Color::Grey => "gry",
+            Color::Blue => "blu",
+            Color::Ambiguous => "amb",
+            Color::Other => "oth",
+            Color::RGB(r, g, b) => return format!("#{:02x}{:02x}{:02x}", r, g, b),
+        };
+        code.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(pub String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot parse \"{}\" as an eye color code, hex color or CSS color name", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+// Accepts anything `from_ecl`, `from_hex` or `from_name` accepts, with the
+// ecl codes in any case. Surrounding whitespace is ignored.
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let trimmed = string.trim();
+        Color::from_ecl(&trimmed.to_ascii_lowercase()).ok()
+            .or_else(|| Color::from_hex(trimmed))
+            .or_else(|| Color::from_name(trimmed))
+            .ok_or_else(|| ParseColorError(string.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, CSS_COLORS, EYE_CSS_NAMES};
+
+    #[test]
+    fn test_css_table_sorted() {
+        assert!(CSS_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_eye_css_names() {
+        for (name, color) in EYE_CSS_NAMES.iter() {
+            assert_eq!(color.rgb(), Color::from_css_name(name).and_then(|c| c.rgb()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_hcl() {
+        assert_eq!(Color::from_hcl("#623a2f").ok(), Some(Color::RGB(0x62, 0x3a, 0x2f)));
+        for bad in ["#623A2F", "#fa0", "623a2f", "#623a2", "#623a2g", "#+23a2f"].iter() {
+            assert!(Color::from_hcl(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = |s: &str| s.parse::<Color>().unwrap();
+        assert_eq!(parse("#623a2f"), Color::RGB(0x62, 0x3a, 0x2f));
+        assert_eq!(parse("#623A2F"), Color::RGB(0x62, 0x3a, 0x2f));
+        assert_eq!(parse("#Fa0"), Color::RGB(0xff, 0xaa, 0x00));
+        assert_eq!(parse("HZL"), Color::Hazel);
+        assert_eq!(parse(" Rebecca Purple "), Color::RGB(0x66, 0x33, 0x99));
+        assert_eq!(parse("light-blue"), Color::RGB(0xad, 0xd8, 0xe6));
+        assert_eq!(parse("blue"), parse("blu"));
+        assert_eq!(parse("Brown"), Color::Brown);
+        assert_eq!(parse("gray"), Color::Grey);
+        assert_eq!(parse("#0000ff").rgb(), parse("blue").rgb());
+        assert_eq!(Color::from_css_name("blue"), Some(Color::RGB(0, 0, 0xff)));
+        for bad in ["#12", "#1234", "#12345g", "623a2f", "hazel", "", "#\u{e9}12"].iter() {
+            assert!(bad.parse::<Color>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Color::Brown.rgb(), Some((0xa5, 0x2a, 0x2a)));
+        assert_eq!(Color::Hazel.rgb(), None);
+        assert_eq!(Color::RGB(0, 255, 255).css_name(), Some("aqua"));
+        assert_eq!(Color::Grey.css_name(), Some("gray"));
+        assert_eq!(Color::RGB(1, 2, 3).css_name(), None);
+        assert_eq!(Color::RGB(1, 2, 3).nearest_css_name(), Some("black"));
+        assert_eq!(Color::RGB(0x62, 0x3a, 0x2f).nearest_css_name(), Some("darkolivegreen"));
+        assert_eq!(Color::Other.nearest_css_name(), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+
+// The 1-based lines a record spans in the batch file, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSpan {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl fmt::Display for LineSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "line {}", self.first)
+        } else {
+            write!(f, "lines {}-{}", self.first, self.last)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    // In the order the fields are declared in the schema
+    MissingFields { fields: Vec<String> },
+    UnexpectedField { field: String, value: String },
+    DuplicateField { field: String, first: String, second: String },
+    // The value could not be parsed at all, e.g. "abc" for a year
+    MalformedValue { field: String, value: String, expected: String, source: Option<ParseIntError> },
+    // The value was parsed, but breaks a constraint, e.g. year 1900
+    OutOfRange { field: String, value: String, expected: String },
+    ParserError { text: String },
+    ReadError(io::Error),
+}
+
+// An error along with where in the batch file it happened. The lines are
+// filled in by the reader, so errors made outside of one have none.
+#[derive(Debug)]
+pub struct ParsePassportError {
+    pub kind: ErrorKind,
+    pub lines: Option<LineSpan>,
+}
+
+impl ParsePassportError {
+    pub fn with_lines(mut self, lines: LineSpan) -> Self {
+        self.lines = Some(lines);
+        self
+    }
+
+    // The field the error is about, if any
+    pub fn field(&self) -> Option<&str> {
+        match &self.kind {
+            ErrorKind::UnexpectedField { field, .. }
+            | ErrorKind::DuplicateField { field, .. }
+            | ErrorKind::MalformedValue { field, .. }
+            | ErrorKind::OutOfRange { field, .. } => Some(field),
+            ErrorKind::MissingFields { fields } => fields.first().map(|s| s.as_str()),
+            ErrorKind::ParserError { .. } | ErrorKind::ReadError(_) => None,
+        }
+    }
+}
+
+impl From<ErrorKind> for ParsePassportError {
+    fn from(kind: ErrorKind) -> Self {
+        ParsePassportError { kind, lines: None }
+    }
+}
+
+impl From<io::Error> for ParsePassportError {
+    fn from(e: io::Error) -> Self {
+        ErrorKind::ReadError(e).into()
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::MissingFields { fields } => write!(f, "missing fields {}", fields.join(", ")),
+            ErrorKind::UnexpectedField { field, value } => {
+                write!(f, "unexpected field {} with value \"{}\"", field, value)
+            }
+            ErrorKind::DuplicateField { field, first, second } => write!(
+                f,
+                "field {} given twice, first as \"{}\", then as \"{}\"",
+                field, first, second
+            ),
+            ErrorKind::MalformedValue { field, value, expected, source } => {
+                write!(f, "field {}: cannot parse \"{}\", expected {}", field, value, expected)?;
+                match source {
+                    Some(e) => write!(f, " ({})", e),
+                    None => Ok(()),
+                }
+            }
+            ErrorKind::OutOfRange { field, value, expected } => {
+                write!(f, "field {}: \"{}\" is out of range, expected {}", field, value, expected)
+            }
+            ErrorKind::ParserError { text } => write!(f, "cannot parse as key-value pair: \"{}\"", text),
+            ErrorKind::ReadError(e) => write!(f, "failed to read: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for ParsePassportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.lines {
+            Some(lines) => write!(f, "{}: {}", lines, self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for ParsePassportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::MalformedValue { source: Some(e), .. } => Some(e),
+            ErrorKind::ReadError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Centimeter,
+    Inch,
+}
+
+impl LengthUnit {
+    // Both units are a whole number of micrometers, which makes conversions
+    // to micrometers and comparisons between units exact.
+    fn micrometers(self) -> u64 {
+        match self {
+            LengthUnit::Centimeter => 10_000,
+            LengthUnit::Inch => 25_400,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Inch => "in",
+        }
+    }
+}
+
+// A whole number of centimeters or inches, which remembers the unit it was
+// given in. Lengths compare by their physical size, so 127cm == 50in.
+#[derive(Debug, Clone, Copy)]
+pub struct Length {
+    value: u32,
+    unit: LengthUnit,
+}
+
+impl Length {
+    pub fn new(value: u32, unit: LengthUnit) -> Self {
+        Length { value, unit }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    pub fn unit(&self) -> LengthUnit {
+        self.unit
+    }
+
+    pub fn micrometers(&self) -> u64 {
+        self.value as u64 * self.unit.micrometers()
+    }
+
+    // The same length in another unit, rounded to the nearest whole unit,
+    // with halves rounded up. Converting to the same unit is exact. Returns
+    // None if the value does not fit, e.g. u32::MAX inches in centimeters.
+    pub fn to_unit(&self, unit: LengthUnit) -> Option<Length> {
+        let per_unit = unit.micrometers();
+        let value = (self.micrometers() + per_unit / 2) / per_unit;
+        Some(Length { value: u32::try_from(value).ok()?, unit })
+    }
+}
+
+impl PartialEq for Length {
+    fn eq(&self, other: &Self) -> bool {
+        self.micrometers() == other.micrometers()
+    }
+}
+
+impl Eq for Length {}
+
+impl Hash for Length {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.micrometers().hash(state)
+    }
+}
+
+impl PartialOrd for Length {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Length {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.micrometers().cmp(&other.micrometers())
+    }
+}
+
+// Prints the length like it was written, e.g. 183cm
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.suffix())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLengthError(pub String);
+
+impl fmt::Display for ParseLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot parse \"{}\" as a number followed by cm or in", self.0)
+    }
+}
+
+impl std::error::Error for ParseLengthError {}
+
+impl FromStr for Length {
+    type Err = ParseLengthError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = || ParseLengthError(string.to_string());
+        let split = string.find(|c: char| !c.is_ascii_digit()).ok_or_else(error)?;
+        let (number, suffix) = string.split_at(split);
+        let unit = match suffix {
+            "cm" => LengthUnit::Centimeter,
+            "in" => LengthUnit::Inch,
+            _ => return Err(error()),
+        };
+        let value = number.parse::<u32>().map_err(|_| error())?;
+        Ok(Length { value, unit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Length, LengthUnit};
+
+    #[test]
+    fn test_parse_display() {
+        for string in ["183cm", "59in", "0cm"].iter() {
+            assert_eq!(string.parse::<Length>().unwrap().to_string(), *string);
+        }
+        for string in ["183", "cm", "183mm", "-1in", "1.5in", "18 3cm"].iter() {
+            assert!(string.parse::<Length>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_compare_units() {
+        let cm = |n| Length::new(n, LengthUnit::Centimeter);
+        let inch = |n| Length::new(n, LengthUnit::Inch);
+        assert_eq!(cm(127), inch(50));
+        assert!(cm(150) < inch(60));
+        assert!(cm(153) > inch(60));
+        assert_eq!(inch(60).micrometers(), 1_524_000);
+    }
+
+    #[test]
+    fn test_convert() {
+        let inch = Length::new(59, LengthUnit::Inch);
+        // 59in is 149.86cm, which used to be truncated to 149cm
+        assert_eq!(inch.to_unit(LengthUnit::Centimeter).unwrap().value(), 150);
+        assert_eq!(inch.to_unit(LengthUnit::Inch).unwrap().value(), 59);
+        assert_eq!(inch.to_unit(LengthUnit::Centimeter).unwrap().unit(), LengthUnit::Centimeter);
+        // 193cm is 75.98in
+        assert_eq!(Length::new(193, LengthUnit::Centimeter).to_unit(LengthUnit::Inch).unwrap().value(), 76);
+        // 25in is exactly 63.5cm
+        assert_eq!(Length::new(25, LengthUnit::Inch).to_unit(LengthUnit::Centimeter).unwrap().value(), 64);
+        assert_eq!(Length::new(127, LengthUnit::Centimeter).to_unit(LengthUnit::Inch).unwrap().value(), 50);
+        assert_eq!(Length::new(u32::MAX, LengthUnit::Inch).to_unit(LengthUnit::Centimeter), None);
+        assert!(Length::new(u32::MAX, LengthUnit::Centimeter).to_unit(LengthUnit::Inch).is_some());
+    }
+}
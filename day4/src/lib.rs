@@ -0,0 +1,369 @@
+#[macro_use] extern crate lazy_static;
+extern crate records;
+extern crate regex;
+
+pub mod color;
+pub mod error;
+pub mod length;
+pub mod policy;
+pub mod report;
+pub mod schema;
+pub mod serialize;
+
+pub use color::Color;
+pub use error::{ErrorKind, LineSpan, ParsePassportError};
+pub use policy::Policy;
+use length::Length;
+use records::RecordReader;
+use schema::PASSPORT_SCHEMA;
+use std::collections::HashMap;
+use std::default::Default;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+
+pub(crate) fn malformed(field: &str, value: &str, expected: &str, source: Option<std::num::ParseIntError>) -> ErrorKind {
+    ErrorKind::MalformedValue {
+        field: field.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
+        source,
+    }
+}
+
+fn parse_number<T>(field: &str, value: &str) -> Result<T, ErrorKind>
+where T: FromStr<Err = std::num::ParseIntError>,
+{
+    value.parse::<T>().map_err(|e| malformed(field, value, "a number", Some(e)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passport {
+    pub byr: u16,
+    pub iyr: u16,
+    pub eyr: u16,
+    pub hgt: Length,
+    pub hcl: Color,
+    pub ecl: Color,
+    pub pid: u64,
+    pub cid: Option<u16>
+}
+
+impl Passport {
+    // Validates with the strict policy, see `from_hashmap_with`
+    pub fn from_hashmap(map: &HashMap<String, String>) -> Result<Passport, ErrorKind> {
+        Passport::from_hashmap_with(map, Policy::Strict, &mut Vec::new())
+    }
+
+    // The values are validated against the passport schema, so converting
+    // them afterwards only fails if the schema and this function disagree.
+    // The schema allows cid to be anything, so a cid that is not a u16 is
+    // left out with a warning under every policy, like the puzzle ignores it.
+    pub fn from_hashmap_with(
+        map: &HashMap<String, String>,
+        policy: Policy,
+        warnings: &mut Vec<ErrorKind>,
+    ) -> Result<Passport, ErrorKind> {
+        PASSPORT_SCHEMA.validate_with(map, policy, warnings)?;
+
+        // Create passport
+        let passport = Passport{
+            byr: parse_number("byr", &map["byr"])?,
+            eyr: parse_number("eyr", &map["eyr"])?,
+            iyr: parse_number("iyr", &map["iyr"])?,
+
+            // This is formatted like \d+cm or \d+in
+            hgt: map["hgt"].parse::<Length>()
+                .map_err(|_| malformed("hgt", &map["hgt"], "a number followed by cm or in", None))?,
+
+            hcl: Color::from_hcl(&map["hcl"])?,
+            ecl: Color::from_ecl(&map["ecl"])?,
+            pid: parse_number("pid", &map["pid"])?,
+
+            cid: match map.get("cid") {
+                Some(str) => match parse_number::<u16>("cid", str) {
+                    Ok(n) => Some(n),
+                    Err(e) => {
+                        warnings.push(e);
+                        None
+                    }
+                }
+                None => Option::None
+            }
+        };
+
+        Ok(passport)
+    }
+}
+
+// The key-value pairs of one record in a batch file, before they are
+// validated, and the lines it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub lines: LineSpan,
+    pub fields: HashMap<String, String>,
+}
+
+// Splits a batch into records separated by blank lines, see the records
+// crate. A record with an error is yielded as a single error spanning all of
+// its lines, so the records after it are unaffected. Lines that are not valid
+// UTF-8 are errors of their record like any other. Any other I/O error ends
+// the iteration after it is yielded.
+// Duplicate fields are handled according to the policy, strict by default.
+pub struct RecordIterator<R: BufRead> {
+    reader: RecordReader<R>,
+    policy: Policy,
+    warnings: Vec<ParsePassportError>,
+}
+
+impl<R: BufRead> RecordIterator<R> {
+    pub fn new(io: R) -> RecordIterator<R> {
+        RecordIterator{reader: RecordReader::new(io), policy: Policy::Strict, warnings: Vec::new()}
+    }
+
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    // Warnings of all records read so far, including records that were
+    // rejected for other reasons
+    pub fn warnings(&self) -> &[ParsePassportError] {
+        &self.warnings
+    }
+
+    // Adds warnings found while validating a record, so they are listed
+    // along with the ones found while reading
+    pub fn add_warnings(&mut self, lines: LineSpan, warnings: Vec<ErrorKind>) {
+        self.warnings.extend(warnings.into_iter().map(|w| ParsePassportError::from(w).with_lines(lines)))
+    }
+}
+
+impl RecordIterator<BufReader<File>> {
+    pub fn from_path(path: &str) -> io::Result<Self> {
+        Ok(RecordIterator::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for RecordIterator<R> {
+    type Item = Result<Record, ParsePassportError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.reader.next_record()? {
+            Ok(record) => record,
+            Err(e) => {
+                let lines = LineSpan{first: e.first, last: e.last};
+                return Some(Err(ParsePassportError::from(e.source).with_lines(lines)))
+            }
+        };
+        let lines = LineSpan{first: record.first, last: record.last};
+        let mut fields = HashMap::new();
+        let mut warnings = Vec::new();
+        for line in record.lines() {
+            if let Err(e) = update_hashmap_with(&mut fields, line, self.policy, &mut warnings) {
+                self.add_warnings(lines, warnings);
+                return Some(Err(ParsePassportError::from(e).with_lines(lines)))
+            }
+        }
+        self.add_warnings(lines, warnings);
+        Some(Ok(Record{lines, fields}))
+    }
+}
+
+pub struct PassportIterator<R: BufRead> {
+    records: RecordIterator<R>,
+}
+
+impl<R: BufRead> PassportIterator<R> {
+    pub fn new(io: R) -> PassportIterator<R> {
+        PassportIterator{records: RecordIterator::new(io)}
+    }
+
+    pub fn with_policy(self, policy: Policy) -> Self {
+        PassportIterator{records: self.records.with_policy(policy)}
+    }
+
+    pub fn warnings(&self) -> &[ParsePassportError] {
+        self.records.warnings()
+    }
+}
+
+impl PassportIterator<BufReader<File>> {
+    pub fn from_path(path: &str) -> io::Result<Self> {
+        Ok(PassportIterator{records: RecordIterator::from_path(path)?})
+    }
+}
+
+impl<R: BufRead> Iterator for PassportIterator<R> {
+    type Item = Result<Passport, ParsePassportError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut warnings = Vec::new();
+        let passport = Passport::from_hashmap_with(&record.fields, self.records.policy, &mut warnings);
+        self.records.add_warnings(record.lines, warnings);
+        Some(passport.map_err(|e| ParsePassportError::from(e).with_lines(record.lines)))
+    }
+}
+
+// Adds the key-value pairs of one line. Fields given twice are errors.
+pub fn update_hashmap(hashmap: &mut HashMap<String, String>, line: &str) -> Result<u32, ErrorKind> {
+    update_hashmap_with(hashmap, line, Policy::Strict, &mut Vec::new())
+}
+
+// Like `update_hashmap`, but fields given twice are handled according to the
+// policy. If tolerated, the last value is kept.
+pub fn update_hashmap_with(
+    hashmap: &mut HashMap<String, String>,
+    line: &str,
+    policy: Policy,
+    warnings: &mut Vec<ErrorKind>,
+) -> Result<u32, ErrorKind> {
+    let mut n_inserts: u32 = 0;
+    for pair in line.split_whitespace() {
+        let parsedpair = parse_keyval_pairs(pair);
+        match parsedpair {
+            Some((key, val)) => {
+                let insertresult = hashmap.insert(key.to_string(), val.to_string());
+                if let Some(first) = insertresult {
+                    policy.tolerate(ErrorKind::DuplicateField{
+                        field: key.to_string(), first, second: val.to_string()
+                    }, warnings)?
+                }
+                n_inserts += 1;
+            },
+            None => {
+                return Err(ErrorKind::ParserError{text: pair.to_string()})
+            }
+        }
+    }
+    Ok(n_inserts)
+}
+
+fn parse_keyval_pairs(string: &str) -> Option<(&str, &str)> {
+    let mut key = Default::default();
+    let mut value = Default::default();
+    for (i, substr) in string.split(':').enumerate() {
+        match i {
+            0 => {key = substr},
+            1 => {value = substr},
+            _ => return None
+        }
+    }
+    Some((key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{update_hashmap, ErrorKind, LineSpan, Passport, PassportIterator, Policy, RecordIterator};
+    use std::collections::HashMap;
+
+    fn parse_map(string: &str) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        update_hashmap(&mut map, string).unwrap();
+        map
+    }
+
+    #[test]
+    fn test_error_context() {
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f";
+        assert!(Passport::from_hashmap(&parse_map(valid)).is_ok());
+
+        match Passport::from_hashmap(&parse_map("pid:087499704 hgt:74in")) {
+            Err(ErrorKind::MissingFields{fields}) => assert_eq!(fields, vec!["byr", "iyr", "eyr", "hcl", "ecl"]),
+            other => panic!("Expected MissingFields, got {:?}", other),
+        }
+        match Passport::from_hashmap(&parse_map(&valid.replace("byr:1980", "byr:1900"))) {
+            Err(ErrorKind::OutOfRange{field, value, expected}) => {
+                assert_eq!((field.as_str(), value.as_str()), ("byr", "1900"));
+                assert_eq!(expected, "a year between 1920 and 2002");
+            },
+            other => panic!("Expected OutOfRange, got {:?}", other),
+        }
+        match Passport::from_hashmap(&parse_map(&valid.replace("hgt:74in", "hgt:7x4in"))) {
+            Err(ErrorKind::MalformedValue{field, value, ..}) => {
+                assert_eq!((field.as_str(), value.as_str()), ("hgt", "7x4in"));
+            },
+            other => panic!("Expected MalformedValue, got {:?}", other),
+        }
+
+        let mut map = parse_map(valid);
+        match update_hashmap(&mut map, "ecl:blu") {
+            Err(ErrorKind::DuplicateField{field, first, second}) => {
+                assert_eq!((field.as_str(), first.as_str(), second.as_str()), ("ecl", "grn", "blu"))
+            },
+            other => panic!("Expected DuplicateField, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_record_recovery() {
+        let batch = "ecl:gry pid:860033327\r\nbyr:1937 ecl:amb\r\niyr:2017\r\n  \r\nhcl:#cfa07d byr:1929\n\n\n\
+                     a:b:c\nhgt:179cm\n\nhcl:#ae17e1 iyr:2013\n";
+        let records: Vec<_> = RecordIterator::new(batch.as_bytes()).collect();
+        assert_eq!(records.len(), 4);
+        let error = records[0].as_ref().unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::DuplicateField{..}));
+        assert_eq!(error.lines, Some(LineSpan{first: 1, last: 3}));
+        let record = records[1].as_ref().unwrap();
+        assert_eq!((record.lines, record.fields.len()), (LineSpan{first: 5, last: 5}, 2));
+        let error = records[2].as_ref().unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::ParserError{..}));
+        assert_eq!(error.lines, Some(LineSpan{first: 8, last: 9}));
+        let record = records[3].as_ref().unwrap();
+        assert_eq!((record.lines, record.fields.len()), (LineSpan{first: 11, last: 11}, 2));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let batch: &[u8] = b"ecl:gry\nbyr:\xff\n\npid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f";
+        let passports: Vec<_> = PassportIterator::new(batch).collect();
+        assert_eq!(passports.len(), 2);
+        let error = passports[0].as_ref().unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::ReadError(_)));
+        assert_eq!(error.lines, Some(LineSpan{first: 1, last: 2}));
+        assert!(passports[1].is_ok());
+    }
+
+    #[test]
+    fn test_policies() {
+        let batch = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f\n\
+                     ecl:blu cid:abc\n\n\
+                     pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f xyz:1\n";
+
+        let strict: Vec<_> = PassportIterator::new(batch.as_bytes()).collect();
+        assert!(matches!(strict[0].as_ref().unwrap_err().kind, ErrorKind::DuplicateField{..}));
+        assert!(matches!(strict[1].as_ref().unwrap_err().kind, ErrorKind::UnexpectedField{..}));
+
+        // An unparsable cid never rejects a passport
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f";
+        for cid in ["cid:abc", "cid:99999"].iter() {
+            let batch = format!("{} {}", valid, cid);
+            let mut strict = PassportIterator::new(batch.as_bytes());
+            assert_eq!(strict.next().unwrap().unwrap().cid, None);
+            assert_eq!(strict.warnings().len(), 1);
+        }
+
+        let mut lenient = PassportIterator::new(batch.as_bytes()).with_policy(Policy::Lenient);
+        let passport = lenient.next().unwrap().unwrap();
+        assert_eq!((passport.ecl, passport.cid), (super::Color::Blue, None));
+        assert!(lenient.next().unwrap().is_ok());
+        let warnings: Vec<String> = lenient.warnings().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings, vec![
+            "lines 1-2: field ecl given twice, first as \"grn\", then as \"blu\"",
+            "lines 1-2: field cid: cannot parse \"abc\", expected a number (invalid digit found in string)",
+            "line 4: unexpected field xyz with value \"1\"",
+        ]);
+
+        let mut permissive = PassportIterator::new(batch.as_bytes()).with_policy(Policy::Permissive);
+        assert_eq!(permissive.by_ref().filter(|p| p.is_ok()).count(), 2);
+        assert_eq!(permissive.warnings().len(), 2);
+    }
+}
@@ -1,269 +1,238 @@
-#[macro_use] extern crate lazy_static;
-extern crate regex;
-
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
-use std::default::Default;
-use std::iter::FromIterator;
+use day4::report::Report;
+use day4::schema::{Schema, PASSPORT_SCHEMA};
+use day4::serialize::{ordered_fields, redact, write_json, write_text};
+use day4::{ErrorKind, ParsePassportError, Passport, PassportIterator, Policy, RecordIterator};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-#[derive(Debug)]
-enum Color {
-    Brown,
-    Hazel,
-    Green,
-    Grey,
-    Blue,
-    Ambiguous,
-    Other,
-    RGB(u8, u8, u8)
-}
-
-impl Color {
-    fn from_ecl(str: &str) -> Result<Color, ParsePassportError> {
-        let color = match str {
-            "brn" => Color::Brown,
-            "hzl" => Color::Hazel,
-            "grn" => Color::Green,
-            "gry" => Color::Grey,
-            "blu" => Color::Blue,
-            "amb" => Color::Ambiguous,
-            "oth" => Color::Other,
-            _ => return Err(ParsePassportError::InvalidValue)
-        };
-            
-        Ok(color)
-    }
-
-    fn from_hcl(str: &str) -> Result<Color, ParsePassportError> {
-        let caps = match RE.captures(str) {
-            Some(c) => c,
-            None => return Err(ParsePassportError::InvalidValue)
-        };
-        let c1 = u8::from_str_radix(caps.get(1).unwrap().as_str(), 16)?;
-        let c2 = u8::from_str_radix(caps.get(2).unwrap().as_str(), 16)?;
-        let c3 = u8::from_str_radix(caps.get(3).unwrap().as_str(), 16)?;
-        Ok(Color::RGB(c1, c2, c3))
-    }
-}
-
-// This should never panic
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"^#([0-9a-f]{2})([0-9a-f]{2})([0-9a-f]{2})$").unwrap();
-    static ref REQUIRED_FIELDS: HashSet<&'static str> = HashSet::from_iter(
-        vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]);
-    static ref OPTIONAL_FIELDS: HashSet<&'static str> = HashSet::from_iter(vec!["cid"]);
-    
-}
-
-fn assert_between<T: std::cmp::PartialOrd>(n: T, low: T, high: T) -> Result<T, ParsePassportError> {
-    if low <= n && n <= high {
-        Ok(n)
-    } else {
-        Err(ParsePassportError::InvalidValue)
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::exit;
+
+const USAGE: &str = "Usage: day4 [--policy strict|lenient|permissive] [--schema SCHEMAFILE] [--normalize text|json [--raw] [--redact FIELD]...]\n    [--report N] [FILE]";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Text,
+    Json,
+}
+
+// FILE may be "-" to read the batch from stdin.
+// The policy decides whether duplicate fields and unknown fields reject a
+// record, see day4::Policy. Tolerated ones, and cids that are left out
+// because they are not numbers, are listed on stderr at the end.
+// With --schema, the records are validated against the fields declared in
+// the schema file instead of as passports.
+// With --normalize, the valid passports are written to stdout in canonical
+// form instead of being counted, either as a batch file or as JSON Lines.
+// With --raw, every record is written as it was read, only reordered.
+// Fields given with --redact are left out, e.g. --redact cid.
+// With --report, every error in every record is counted per field and reason,
+// and the first N invalid records are listed with their line numbers. The
+// policy applies as when counting.
+// --normalize cannot be combined with --schema or --report, and --raw and
+// --redact need --normalize.
+fn main() {
+    let mut path = None;
+    let mut schema = None;
+    let mut normalize = None;
+    let mut raw = false;
+    let mut redacted: Vec<String> = Vec::new();
+    let mut report = None;
+    let mut policy = Policy::Strict;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--normalize" => {
+                normalize = match args.next().as_deref() {
+                    Some("text") => Some(Format::Text),
+                    Some("json") => Some(Format::Json),
+                    _ => usage(),
+                }
+            }
+            "--raw" => raw = true,
+            "--policy" => {
+                policy = args.next().unwrap_or_else(|| usage()).parse().unwrap_or_else(|e| {
+                    println!("{}", e);
+                    exit(64)
+                })
+            }
+            "--report" => {
+                let limit = args.next().and_then(|n| n.parse::<usize>().ok()).unwrap_or_else(|| usage());
+                report = Some(Report::new(limit))
+            }
+            "--redact" => redacted.push(args.next().unwrap_or_else(|| usage())),
+            "--schema" => {
+                let schemapath = args.next().unwrap_or_else(|| usage());
+                schema = Some(Schema::from_path(&schemapath).unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    exit(65)
+                }))
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
     }
-}
-
-#[derive(Debug)]
-struct Passport {
-    byr: u16,
-    iyr: u16,
-    eyr: u16,
-    hgt: u16, // in centimeters
-    hcl: Color,
-    ecl: Color,
-    pid: u64,
-    cid: Option<u16>
-}
-
-// How do I prevent throwing all the relevant information away
-// when an error is converted to ParsePassportError?
-#[derive(Debug, Clone)]
-enum ParsePassportError {
-    MissingField,
-    UnexpectedField,
-    DuplicateField,
-    InvalidValue,
-    ParserError(String),
-    ReadError,
-}
-
-impl From<std::num::ParseIntError> for ParsePassportError {
-    fn from(_: std::num::ParseIntError) -> Self {
-        ParsePassportError::InvalidValue
+    let path = path.unwrap_or_else(|| "input.txt".to_string());
+    if normalize.is_some() && (schema.is_some() || report.is_some()) {
+        println!("--normalize only writes passports, it cannot be combined with --schema or --report");
+        exit(64)
     }
-}
-
-impl From<std::io::Error> for ParsePassportError {
-    fn from(_: std::io::Error) -> Self {
-        ParsePassportError::ReadError
+    if normalize.is_none() && (raw || !redacted.is_empty()) {
+        println!("--raw and --redact only apply to --normalize");
+        exit(64)
     }
-}
-
-impl Passport {
-    fn from_hashmap(map: &HashMap<String, String>) -> Result<Passport, ParsePassportError> {
-        let keyset: HashSet<&str> = HashSet::from_iter(map.keys().map(|s| s.as_ref()));
-        let diff: HashSet<&str> = REQUIRED_FIELDS.difference(&keyset).copied().collect();
-        
-        // Must must contain required fields
-        if diff.len() != 0 {
-            return Result::Err(ParsePassportError::MissingField)
-        }
-
-        // Must not contain other fields
-        let nonrequired: HashSet<&str> = keyset.difference(&REQUIRED_FIELDS).copied().collect();
-        let superfluous: HashSet<&str> = nonrequired.difference(&OPTIONAL_FIELDS).copied().collect();
-        if superfluous.len() != 0 {
-            return Result::Err(ParsePassportError::UnexpectedField)
+    if let Some(format) = normalize {
+        match write_normalized(&path, format, policy, raw, &redacted) {
+            Ok(unreadable) => exit_if_unreadable(unreadable),
+            Err(e) => {
+                eprintln!("Failed to write output: {}", e);
+                exit(74)
+            }
         }
-
-        // Create passport
-        let passport = Passport{
-            byr: assert_between(map.get("byr").unwrap().parse::<u16>()?, 1920, 2002)?,
-            eyr: assert_between(map.get("eyr").unwrap().parse::<u16>()?, 2020, 2030)?,
-            iyr: assert_between(map.get("iyr").unwrap().parse::<u16>()?, 2010, 2020)?,
-
-            // This should be formatted like \d+cm or \d+in
-            hgt: {
-                let hgtstring = map.get("hgt").unwrap();
-                if let Some((i, _)) = hgtstring.char_indices().rev().nth(1) {
-                    let (factor, low, high) = match &hgtstring[i..] {
-                        "cm" => (1.0, 150, 193),
-                        "in" => (2.54, 59, 76),
-                        _ => return Err(ParsePassportError::InvalidValue)
-                    };
-                    
-                    let value = assert_between(hgtstring[..i].parse::<u8>()?, low, high)?;
-                    (factor * (value as f64).round()) as u16    
-                } else {
-                    return Err(ParsePassportError::InvalidValue)
-                }
-            },
-
-            hcl: Color::from_hcl(&map.get("hcl").unwrap())?,
-            ecl: Color::from_ecl(&map.get("ecl").unwrap())?,
-            
-            pid: {
-                let pid = &map.get("pid").unwrap();
-                if pid.len() != 9 {
-                    return Err(ParsePassportError::InvalidValue)
-                
-                };
-                pid.parse::<u64>()?
-            },
-            
-            cid: match map.get("cid") {
-                Some(str) => {
-                    match str.parse::<u16>() {
-                        Ok(n) => Some(n),
-                        Err(_) => None
-                    }
-                }
-                None => Option::None
+        return
+    }
+    if let Some(mut report) = report {
+        let schema = schema.as_ref().unwrap_or(&PASSPORT_SCHEMA);
+        let mut records = RecordIterator::new(open(&path)).with_policy(policy);
+        while let Some(result) = records.next() {
+            let lines = result.as_ref().ok().map(|record| record.lines);
+            let mut warnings = Vec::new();
+            report.add(result, schema, policy, &mut warnings);
+            if let Some(lines) = lines {
+                records.add_warnings(lines, warnings);
             }
-        };
-
-        return Ok(passport)
+        }
+        print!("{}", report);
+        print_warnings(records.warnings());
+        return
+    }
+    let (n, unreadable) = match schema {
+        None => count_valid_passports(&path, policy),
+        Some(schema) => count_valid_records(&path, &schema, policy),
+    };
+    println!("{:?}", n);
+    exit_if_unreadable(unreadable)
+}
+
+// Records that could not be read are reported as they are found, and make
+// the run fail at the end
+fn exit_if_unreadable(unreadable: usize) {
+    if unreadable > 0 {
+        eprintln!("{} records could not be read", unreadable);
+        exit(65)
     }
 }
 
-struct PassportIterator {
-    io: BufReader<File>,
-    map: HashMap<String, String>,
-    linenumber: usize
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(64)
 }
 
-impl PassportIterator {
-    fn from_path(string: &str) -> PassportIterator {
-        let iobuf = BufReader::new(File::open(string).expect("Failed to open file"));
-        PassportIterator{io: iobuf, map: HashMap::<String, String>::new(), linenumber: 0}
+// Reads from stdin if the path is "-"
+fn open(path: &str) -> Box<dyn BufRead> {
+    if path == "-" {
+        return Box::new(BufReader::new(io::stdin()))
+    }
+    match File::open(path) {
+        Ok(file) => Box::new(BufReader::new(file)),
+        Err(e) => {
+            eprintln!("Failed to open {}: {}", path, e);
+            exit(66)
+        }
     }
 }
 
-impl Iterator for PassportIterator {
-    type Item = (usize, Result<Passport, ParsePassportError>);
-    
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut linebuffer: String = String::new();
-        let mut yieldpassport = false;
-        loop {
-            if let 0 = self.io.read_line(&mut linebuffer).expect("Failed to read line") {
-                if !self.map.is_empty() {
-                    yieldpassport = true;
-                } else {
-                    return None
-                }
+// Returns the number of records that could not be read
+fn write_normalized(path: &str, format: Format, policy: Policy, raw: bool, redacted: &[String]) -> io::Result<usize> {
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut first = true;
+    let mut unreadable = 0;
+    let mut records = RecordIterator::new(open(path)).with_policy(policy);
+    while let Some(result) = records.next() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {unreadable += check_error(e) as usize; continue},
+        };
+        let mut fields = if raw {
+            ordered_fields(&record.fields)
+        } else {
+            let mut warnings = Vec::new();
+            let passport = Passport::from_hashmap_with(&record.fields, policy, &mut warnings);
+            records.add_warnings(record.lines, warnings);
+            match passport {
+                Ok(passport) => passport.to_fields(),
+                Err(e) => {unreadable += check_error(ParsePassportError::from(e).with_lines(record.lines)) as usize; continue},
             }
-            self.linenumber += 1;
-
-            if linebuffer.trim().is_empty() && !self.map.is_empty() {
-                yieldpassport = true;
-            } else {
-                let update = update_hashmap(&mut self.map, &linebuffer); 
-                linebuffer.clear();
-                match update {
-                    Ok(_n) => (),
-                    Err(e) => return Some((self.linenumber, Err(e)))
+        };
+        redact(&mut fields, redacted);
+        match format {
+            Format::Text => {
+                if !first {
+                    writeln!(out)?;
                 }
+                write_text(&mut out, &fields)?
             }
-            if yieldpassport {
-                let passportresult = Passport::from_hashmap(&mut self.map);
-                self.map.clear();
-                return Some((self.linenumber, passportresult))
-            }
+            Format::Json => write_json(&mut out, &fields)?,
         }
+        first = false;
     }
+    print_warnings(records.warnings());
+    out.flush()?;
+    Ok(unreadable)
 }
 
-fn main() {
-    println!("{:?}", count_valid_passports("input.txt"));
-}
-
-fn count_valid_passports(path: &str) -> u32 {
+// Returns the number of valid records, and of records that could not be read
+fn count_valid_passports(path: &str, policy: Policy) -> (u32, usize) {
     let mut n: u32 = 0;
-    let iter = PassportIterator::from_path(path).into_iter();
-    for (linenumber, result) in iter {
+    let mut unreadable = 0;
+    let mut passports = PassportIterator::new(open(path)).with_policy(policy);
+    for result in passports.by_ref() {
         match result {
             Ok(_p) => {n += 1},
-            Err(ParsePassportError::MissingField) => {},
-            Err(ParsePassportError::InvalidValue) => {},
-            Err(e) => panic!("{:?} near line {}", e, linenumber)
+            Err(e) => unreadable += check_error(e) as usize,
         }
     }
-    n
+    print_warnings(passports.warnings());
+    (n, unreadable)
 }
 
-fn update_hashmap(hashmap: &mut HashMap<String, String>, line: &str) -> Result<u32, ParsePassportError> {
-    let mut n_inserts: u32 = 0;
-    for pair in line.split_whitespace() {
-        let parsedpair = parse_keyval_pairs(pair);
-        match parsedpair {
-            Some((key, val)) => {
-                let insertresult = hashmap.insert(key.to_string(), val.to_string());
-                if let Some(_value) = insertresult {
-                    println!("Field is {}", key);
-                    return Err(ParsePassportError::DuplicateField)
-                }
-                n_inserts += 1;
-            },
-            None => {
-                return Err(ParsePassportError::ParserError(format!("Cannot parse as key-value pair {}", pair)))
-            }
+fn count_valid_records(path: &str, schema: &Schema, policy: Policy) -> (u32, usize) {
+    let mut n: u32 = 0;
+    let mut unreadable = 0;
+    let mut records = RecordIterator::new(open(path)).with_policy(policy);
+    while let Some(result) = records.next() {
+        let valid = result.and_then(|record| {
+            let mut warnings = Vec::new();
+            let valid = schema.validate_with(&record.fields, policy, &mut warnings);
+            records.add_warnings(record.lines, warnings);
+            valid.map_err(|e| ParsePassportError::from(e).with_lines(record.lines))
+        });
+        match valid {
+            Ok(()) => {n += 1},
+            Err(e) => unreadable += check_error(e) as usize,
         }
     }
-    return Ok(n_inserts)
+    print_warnings(records.warnings());
+    (n, unreadable)
+}
+
+fn print_warnings(warnings: &[ParsePassportError]) {
+    for warning in warnings.iter() {
+        eprintln!("Warning: {}", warning)
+    }
 }
 
-fn parse_keyval_pairs(string: &str) -> Option<(&str, &str)> {
-    let mut key = Default::default();
-    let mut value = Default::default();
-    for (i, substr) in string.split(':').enumerate() {
-        match i {
-            0 => {key = substr},
-            1 => {value = substr},
-            _ => return None
+// Invalid records are expected. Unexpected and duplicate fields only get
+// here if the policy rejects them. Records that could not be read are
+// printed, and the reader goes on with the next one. Returns whether the
+// record could not be read.
+fn check_error(e: ParsePassportError) -> bool {
+    match e.kind {
+        ErrorKind::MissingFields{..} => false,
+        ErrorKind::MalformedValue{..} | ErrorKind::OutOfRange{..} => false,
+        ErrorKind::UnexpectedField{..} | ErrorKind::DuplicateField{..} => false,
+        ErrorKind::ParserError{..} | ErrorKind::ReadError(_) => {
+            eprintln!("{}", e);
+            true
         }
     }
-    return Some((key, value))
 }
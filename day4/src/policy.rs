@@ -0,0 +1,46 @@
+use crate::error::ErrorKind;
+use std::str::FromStr;
+
+// What to do about irregularities that do not make a record unusable:
+// fields given twice and fields not in the schema. Missing fields and
+// invalid values are always errors, except an unparsable cid, which is
+// always only a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Policy {
+    // Reject the record
+    #[default]
+    Strict,
+    // Keep the record with a warning. Of a duplicate field, the last value is
+    // kept.
+    Lenient,
+    // Like lenient, but fields not in the schema are ignored without warning
+    Permissive,
+}
+
+impl Policy {
+    // Returns the error back if the policy rejects it, or else adds it to the
+    // warnings, unless the policy ignores it. Only call this with errors the
+    // caller knows how to recover from.
+    pub fn tolerate(&self, error: ErrorKind, warnings: &mut Vec<ErrorKind>) -> Result<(), ErrorKind> {
+        match (self, &error) {
+            (Policy::Strict, _) => return Err(error),
+            (Policy::Permissive, ErrorKind::UnexpectedField { .. }) => (),
+            (Policy::Lenient, _) | (Policy::Permissive, _) => warnings.push(error),
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "strict" => Ok(Policy::Strict),
+            "lenient" => Ok(Policy::Lenient),
+            "permissive" => Ok(Policy::Permissive),
+            _ => Err(format!("Unknown policy \"{}\", expected strict, lenient or permissive", string)),
+        }
+    }
+}
+
@@ -0,0 +1,199 @@
+use crate::error::{ErrorKind, LineSpan, ParsePassportError};
+use crate::schema::Schema;
+use crate::{Policy, Record};
+use std::collections::HashMap;
+use std::fmt;
+
+// How often one field was wrong, by reason
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldFailures {
+    pub missing: usize,
+    pub malformed: usize,
+    pub out_of_range: usize,
+    pub unexpected: usize,
+    pub duplicate: usize,
+}
+
+impl FieldFailures {
+    pub fn total(&self) -> usize {
+        self.missing + self.malformed + self.out_of_range + self.unexpected + self.duplicate
+    }
+}
+
+// A record that failed validation, with every error found in it
+#[derive(Debug)]
+pub struct Offender {
+    pub lines: Option<LineSpan>,
+    pub errors: Vec<ErrorKind>,
+}
+
+impl fmt::Display for Offender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(lines) = self.lines {
+            write!(f, "{}: ", lines)?;
+        }
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+// Statistics over a whole batch. Unlike counting valid passports, every
+// error found in a record is counted, not just the first.
+#[derive(Debug)]
+pub struct Report {
+    pub records: usize,
+    pub valid: usize,
+    // Records that could not be split into key-value pairs or read at all
+    pub unreadable: usize,
+    pub fields: HashMap<String, FieldFailures>,
+    // The first `limit` records that failed, in batch order
+    pub offenders: Vec<Offender>,
+    pub limit: usize,
+}
+
+impl Report {
+    pub fn new(limit: usize) -> Report {
+        Report{records: 0, valid: 0, unreadable: 0, fields: HashMap::new(), offenders: Vec::new(), limit}
+    }
+
+    // Adds one result of a `RecordIterator`. Unexpected fields the policy
+    // tolerates are added to the warnings instead of counted, like
+    // `Schema::validate_with` does.
+    pub fn add(
+        &mut self,
+        result: Result<Record, ParsePassportError>,
+        schema: &Schema,
+        policy: Policy,
+        warnings: &mut Vec<ErrorKind>,
+    ) {
+        match result {
+            Ok(record) => {
+                let errors = schema.errors(&record.fields).into_iter()
+                    .filter_map(|error| match error {
+                        ErrorKind::UnexpectedField { .. } => policy.tolerate(error, warnings).err(),
+                        _ => Some(error),
+                    })
+                    .collect();
+                self.add_errors(Some(record.lines), errors)
+            }
+            Err(e) => self.add_errors(e.lines, vec![e.kind]),
+        }
+    }
+
+    fn add_errors(&mut self, lines: Option<LineSpan>, errors: Vec<ErrorKind>) {
+        self.records += 1;
+        if errors.is_empty() {
+            self.valid += 1;
+            return
+        }
+        for error in errors.iter() {
+            let mut count = |field: &str, reason: fn(&mut FieldFailures) -> &mut usize| {
+                *reason(self.fields.entry(field.to_string()).or_default()) += 1
+            };
+            match error {
+                ErrorKind::MissingFields { fields } => {
+                    for field in fields.iter() {
+                        count(field, |f| &mut f.missing)
+                    }
+                }
+                ErrorKind::MalformedValue { field, .. } => count(field, |f| &mut f.malformed),
+                ErrorKind::OutOfRange { field, .. } => count(field, |f| &mut f.out_of_range),
+                ErrorKind::UnexpectedField { field, .. } => count(field, |f| &mut f.unexpected),
+                ErrorKind::DuplicateField { field, .. } => count(field, |f| &mut f.duplicate),
+                ErrorKind::ParserError { .. } | ErrorKind::ReadError(_) => self.unreadable += 1,
+            }
+        }
+        if self.offenders.len() < self.limit {
+            self.offenders.push(Offender{lines, errors})
+        }
+    }
+
+    // Fields with at least one failure, most failures first
+    pub fn worst_fields(&self) -> Vec<(&str, FieldFailures)> {
+        let mut fields: Vec<(&str, FieldFailures)> = self.fields.iter()
+            .map(|(name, failures)| (name.as_str(), *failures))
+            .collect();
+        fields.sort_by(|(a, fa), (b, fb)| fb.total().cmp(&fa.total()).then(a.cmp(b)));
+        fields
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} records, {} valid, {} invalid", self.records, self.valid, self.records - self.valid)?;
+        if self.unreadable > 0 {
+            writeln!(f, "{} records could not be parsed", self.unreadable)?;
+        }
+        if !self.fields.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "field  missing  malformed  out of range  unexpected  duplicate")?;
+            for (name, c) in self.worst_fields() {
+                writeln!(f, "{:<5}  {:>7}  {:>9}  {:>12}  {:>10}  {:>9}",
+                    name, c.missing, c.malformed, c.out_of_range, c.unexpected, c.duplicate)?;
+            }
+        }
+        if !self.offenders.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "First {} invalid records:", self.offenders.len())?;
+            for offender in self.offenders.iter() {
+                writeln!(f, "{}", offender)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldFailures, Report};
+    use crate::error::{ErrorKind, LineSpan, ParsePassportError};
+    use crate::schema::PASSPORT_SCHEMA;
+    use crate::{update_hashmap, Policy, Record};
+    use std::collections::HashMap;
+
+    fn record(first: usize, line: &str) -> Result<Record, ParsePassportError> {
+        let mut fields = HashMap::new();
+        update_hashmap(&mut fields, line).unwrap();
+        Ok(Record{lines: LineSpan{first, last: first}, fields})
+    }
+
+    #[test]
+    fn test_report() {
+        let mut report = Report::new(2);
+        let mut warnings = Vec::new();
+        let mut add = |report: &mut Report, result| report.add(result, &PASSPORT_SCHEMA, Policy::Strict, &mut warnings);
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f";
+        add(&mut report, record(1, valid));
+        add(&mut report, record(3, "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030"));
+        add(&mut report, record(5, &valid.replace("byr:1980", "byr:1900").replace("hgt:74in", "hgt:74")));
+        let error = ParsePassportError::from(ErrorKind::ParserError{text: "x".to_string()});
+        add(&mut report, Err(error.with_lines(LineSpan{first: 7, last: 8})));
+        assert!(warnings.is_empty());
+
+        assert_eq!((report.records, report.valid, report.unreadable), (4, 1, 1));
+        assert_eq!(report.fields["byr"], FieldFailures{missing: 1, out_of_range: 1, ..Default::default()});
+        assert_eq!(report.fields["hgt"], FieldFailures{malformed: 1, ..Default::default()});
+        assert_eq!(report.fields["hcl"].total(), 1);
+        assert_eq!(report.worst_fields()[0].0, "byr");
+
+        assert_eq!(report.offenders.len(), 2);
+        assert_eq!(report.offenders[0].to_string(), "line 3: missing fields byr, hcl");
+        assert_eq!(report.offenders[1].lines, Some(LineSpan{first: 5, last: 5}));
+        assert_eq!(report.offenders[1].errors.len(), 2);
+    }
+
+    #[test]
+    fn test_report_policy() {
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f";
+        let extra = format!("{} xyz:1", valid);
+        let add = |policy| {
+            let mut report = Report::new(1);
+            let mut warnings = Vec::new();
+            report.add(record(1, &extra), &PASSPORT_SCHEMA, policy, &mut warnings);
+            (report.valid, report.fields.get("xyz").map(|f| f.unexpected), warnings.len())
+        };
+        assert_eq!(add(Policy::Strict), (0, Some(1), 0));
+        assert_eq!(add(Policy::Lenient), (1, None, 1));
+        assert_eq!(add(Policy::Permissive), (1, None, 0));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::error::ErrorKind;
+use crate::policy::Policy;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+
+lazy_static! {
+    // This should never panic
+    pub static ref PASSPORT_SCHEMA: Schema = Schema::parse(include_str!("passport.schema")).unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Year { low: u32, high: u32 },
+    // Bounds for each unit the number may be followed by
+    Length { units: Vec<(String, u32, u32)> },
+    HexColor,
+    Enum { values: Vec<String> },
+    Digits { width: usize },
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: String,
+    pub required: bool,
+    pub kind: FieldType,
+}
+
+// Which fields a document has, and what their values must look like.
+// See passport.schema for the file format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    pub fields: Vec<FieldSpec>,
+}
+
+// 1-based line of the schema file, and what is wrong with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Schema line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+fn parse_bounds(string: &str) -> Option<(u32, u32)> {
+    let mut fields = string.split('-');
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(low), Some(high), None) => {
+            let (low, high) = (low.parse().ok()?, high.parse().ok()?);
+            if low <= high { Some((low, high)) } else { None }
+        }
+        _ => None,
+    }
+}
+
+impl FieldType {
+    fn parse(kind: &str, params: &[&str]) -> Result<FieldType, String> {
+        let bad_params = || format!("Invalid parameters for {}: \"{}\"", kind, params.join(" "));
+        let fieldtype = match (kind, params) {
+            ("year", [bounds]) => {
+                let (low, high) = parse_bounds(bounds).ok_or_else(bad_params)?;
+                FieldType::Year { low, high }
+            }
+            ("length", units) if !units.is_empty() => {
+                let mut parsed = Vec::new();
+                for unit in units.iter() {
+                    let mut parts = unit.splitn(2, ':');
+                    let (name, bounds) = match (parts.next(), parts.next()) {
+                        (Some(name), Some(bounds)) if !name.is_empty() => (name, bounds),
+                        _ => return Err(bad_params()),
+                    };
+                    let (low, high) = parse_bounds(bounds).ok_or_else(bad_params)?;
+                    parsed.push((name.to_string(), low, high));
+                }
+                FieldType::Length { units: parsed }
+            }
+            ("hexcolor", []) => FieldType::HexColor,
+            ("enum", values) if !values.is_empty() => FieldType::Enum {
+                values: values.iter().map(|s| s.to_string()).collect(),
+            },
+            ("digits", [width]) => FieldType::Digits {
+                width: width.parse().map_err(|_| bad_params())?,
+            },
+            ("any", []) => FieldType::Any,
+            ("year", _) | ("length", _) | ("hexcolor", _) | ("enum", _) | ("digits", _) | ("any", _) => {
+                return Err(bad_params())
+            }
+            _ => return Err(format!("Unknown type \"{}\"", kind)),
+        };
+        Ok(fieldtype)
+    }
+
+    // Description of valid values, for error messages
+    pub fn expected(&self) -> String {
+        match self {
+            FieldType::Year { low, high } => format!("a year between {} and {}", low, high),
+            FieldType::Length { units } => {
+                let units: Vec<String> = units
+                    .iter()
+                    .map(|(unit, low, high)| format!("{}-{}{}", low, high, unit))
+                    .collect();
+                format!("a length of {}", units.join(" or "))
+            }
+            FieldType::HexColor => "# followed by 6 lowercase hex digits".to_string(),
+            FieldType::Enum { values } => format!("one of {}", values.join(", ")),
+            FieldType::Digits { width } => format!("exactly {} digits", width),
+            FieldType::Any => "anything".to_string(),
+        }
+    }
+
+    pub fn check(&self, field: &str, value: &str) -> Result<(), ErrorKind> {
+        let malformed = |source| ErrorKind::MalformedValue {
+            field: field.to_string(),
+            value: value.to_string(),
+            expected: self.expected(),
+            source,
+        };
+        let out_of_range = || ErrorKind::OutOfRange {
+            field: field.to_string(),
+            value: value.to_string(),
+            expected: self.expected(),
+        };
+        match self {
+            FieldType::Year { low, high } => {
+                let n = value.parse::<u32>().map_err(|e| malformed(Some(e)))?;
+                if n < *low || n > *high {
+                    return Err(out_of_range());
+                }
+            }
+            FieldType::Length { units } => {
+                let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                let (_unit, low, high) = units
+                    .iter()
+                    .find(|(u, _low, _high)| u == unit)
+                    .ok_or_else(|| malformed(None))?;
+                let n = number.parse::<u32>().map_err(|e| malformed(Some(e)))?;
+                if n < *low || n > *high {
+                    return Err(out_of_range());
+                }
+            }
+            FieldType::HexColor => {
+                let hex = value.strip_prefix('#').ok_or_else(|| malformed(None))?;
+                if hex.len() != 6 || !hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) {
+                    return Err(malformed(None));
+                }
+            }
+            FieldType::Enum { values } => {
+                if !values.iter().any(|v| v == value) {
+                    return Err(malformed(None));
+                }
+            }
+            FieldType::Digits { width } => {
+                if value.len() != *width || !value.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(malformed(None));
+                }
+            }
+            FieldType::Any => (),
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    pub fn parse(string: &str) -> Result<Schema, SchemaError> {
+        let mut fields: Vec<FieldSpec> = Vec::new();
+        for (i, line) in string.lines().enumerate() {
+            let error = |message: String| SchemaError { line: i + 1, message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() < 3 {
+                return Err(error("Expected a name, required or optional, and a type".to_string()));
+            }
+            let name = words[0].to_string();
+            if fields.iter().any(|f| f.name == name) {
+                return Err(error(format!("Field {} is declared twice", name)));
+            }
+            let required = match words[1] {
+                "required" => true,
+                "optional" => false,
+                other => return Err(error(format!("Expected required or optional, got \"{}\"", other))),
+            };
+            let kind = FieldType::parse(words[2], &words[3..]).map_err(error)?;
+            fields.push(FieldSpec { name, required, kind });
+        }
+        Ok(Schema { fields })
+    }
+
+    pub fn from_path(path: &str) -> Result<Schema, SchemaError> {
+        let string = fs::read_to_string(path).map_err(|e| SchemaError {
+            line: 0,
+            message: format!("Failed to read {}: {}", path, e),
+        })?;
+        Schema::parse(&string)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FieldSpec> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    // Checks that all required fields are present, that there are no fields
+    // not in the schema, and that each value is valid for its field, in that
+    // order. Fields are checked in the order they are declared.
+    pub fn validate(&self, map: &HashMap<String, String>) -> Result<(), ErrorKind> {
+        match self.errors(map).into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    // Like `validate`, but fields not in the schema are handled according to
+    // the policy, and the tolerated ones are added to the warnings.
+    pub fn validate_with(
+        &self,
+        map: &HashMap<String, String>,
+        policy: Policy,
+        warnings: &mut Vec<ErrorKind>,
+    ) -> Result<(), ErrorKind> {
+        for error in self.errors(map) {
+            match error {
+                ErrorKind::UnexpectedField { .. } => policy.tolerate(error, warnings)?,
+                _ => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    // Like `validate`, but keeps going after the first error and returns all
+    // of them. Missing fields are reported together as one error.
+    pub fn errors(&self, map: &HashMap<String, String>) -> Vec<ErrorKind> {
+        let mut errors = Vec::new();
+        let missing: Vec<String> = self
+            .fields
+            .iter()
+            .filter(|f| f.required && !map.contains_key(&f.name))
+            .map(|f| f.name.clone())
+            .collect();
+        if !missing.is_empty() {
+            errors.push(ErrorKind::MissingFields { fields: missing });
+        }
+
+        let known: HashSet<&str> = self.fields.iter().map(|f| f.name.as_str()).collect();
+        let mut unknown: Vec<&String> = map.keys().filter(|k| !known.contains(k.as_str())).collect();
+        unknown.sort_unstable();
+        for field in unknown {
+            errors.push(ErrorKind::UnexpectedField {
+                field: field.to_string(),
+                value: map[field].clone(),
+            });
+        }
+
+        for spec in self.fields.iter() {
+            if let Some(value) = map.get(&spec.name) {
+                if let Err(e) = spec.kind.check(&spec.name, value) {
+                    errors.push(e);
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldType, Schema, PASSPORT_SCHEMA};
+    use crate::error::ErrorKind;
+    use std::collections::HashMap;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_passport_schema() {
+        assert_eq!(PASSPORT_SCHEMA.fields.len(), 8);
+        assert_eq!(
+            PASSPORT_SCHEMA.get("hgt").unwrap().kind,
+            FieldType::Length { units: vec![("cm".to_string(), 150, 193), ("in".to_string(), 59, 76)] }
+        );
+        assert!(!PASSPORT_SCHEMA.get("cid").unwrap().required);
+    }
+
+    #[test]
+    fn test_field_types() {
+        let hgt = &PASSPORT_SCHEMA.get("hgt").unwrap().kind;
+        assert!(hgt.check("hgt", "60in").is_ok());
+        assert!(hgt.check("hgt", "190cm").is_ok());
+        assert!(matches!(hgt.check("hgt", "190in"), Err(ErrorKind::OutOfRange { .. })));
+        assert!(matches!(hgt.check("hgt", "190"), Err(ErrorKind::MalformedValue { .. })));
+        assert!(matches!(hgt.check("hgt", "cm"), Err(ErrorKind::MalformedValue { source: Some(_), .. })));
+
+        let hcl = &PASSPORT_SCHEMA.get("hcl").unwrap().kind;
+        assert!(hcl.check("hcl", "#123abc").is_ok());
+        assert!(hcl.check("hcl", "#123abz").is_err());
+        assert!(hcl.check("hcl", "123abc").is_err());
+
+        let pid = &PASSPORT_SCHEMA.get("pid").unwrap().kind;
+        assert!(pid.check("pid", "000000001").is_ok());
+        assert!(pid.check("pid", "0123456789").is_err());
+        assert!(PASSPORT_SCHEMA.get("ecl").unwrap().kind.check("ecl", "wat").is_err());
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema = Schema::parse("# A ticket\nid required digits 4\n\nseat optional enum A B\n").unwrap();
+        assert!(schema.validate(&map(&[("id", "0042")])).is_ok());
+        assert!(schema.validate(&map(&[("id", "0042"), ("seat", "B")])).is_ok());
+        match schema.validate(&map(&[("seat", "A")])) {
+            Err(ErrorKind::MissingFields { fields }) => assert_eq!(fields, vec!["id"]),
+            other => panic!("Expected MissingFields, got {:?}", other),
+        }
+        match schema.validate(&map(&[("id", "0042"), ("row", "1")])) {
+            Err(ErrorKind::UnexpectedField { field, .. }) => assert_eq!(field, "row"),
+            other => panic!("Expected UnexpectedField, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        assert_eq!(Schema::parse("a required\n").unwrap_err().line, 1);
+        assert_eq!(Schema::parse("a required any\na optional any\n").unwrap_err().line, 2);
+        assert_eq!(Schema::parse("\na sometimes any\n").unwrap_err().line, 2);
+        assert!(Schema::parse("a required year 2000\n").is_err());
+        assert!(Schema::parse("a required year 2010-2000\n").is_err());
+        assert!(Schema::parse("a required length cm150-190\n").is_err());
+        assert!(Schema::parse("a required color\n").is_err());
+        assert!(Schema::parse("a required hexcolor 6\n").is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::schema::PASSPORT_SCHEMA;
+use crate::Passport;
+use json::json_string;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+// Key-value pairs of a record, in the order they are written
+pub type Fields = Vec<(String, String)>;
+
+impl Passport {
+    // The fields as they would be written in a batch file. Parsing them with
+    // `Passport::from_hashmap` gives back the same passport.
+    pub fn to_fields(&self) -> Fields {
+        let mut fields = vec![
+            ("byr", self.byr.to_string()),
+            ("iyr", self.iyr.to_string()),
+            ("eyr", self.eyr.to_string()),
+            ("hgt", self.hgt.to_string()),
+            ("hcl", self.hcl.to_field_value()),
+            ("ecl", self.ecl.to_field_value()),
+            // The schema requires exactly 9 digits, including leading zeros
+            ("pid", format!("{:09}", self.pid)),
+        ];
+        if let Some(cid) = self.cid {
+            fields.push(("cid", cid.to_string()));
+        }
+        fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
+
+// Orders the fields of a raw record like the passport schema declares them,
+// followed by any other fields sorted by name.
+pub fn ordered_fields(map: &HashMap<String, String>) -> Fields {
+    let position = |key: &str| PASSPORT_SCHEMA.fields.iter().position(|f| f.name == key);
+    let mut fields: Fields = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    fields.sort_by(|(a, _), (b, _)| match (position(a), position(b)) {
+        (Some(i), Some(j)) => i.cmp(&j),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.cmp(b),
+    });
+    fields
+}
+
+// Removes the given fields, e.g. cid
+pub fn redact(fields: &mut Fields, redacted: &[String]) {
+    fields.retain(|(key, _)| !redacted.contains(key))
+}
+
+// Writes the record on a single line, like "byr:1937 iyr:2017 ..."
+// Records in a batch must be separated by a blank line.
+pub fn write_text<W: Write>(out: &mut W, fields: &[(String, String)]) -> io::Result<()> {
+    let pairs: Vec<String> = fields.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+    writeln!(out, "{}", pairs.join(" "))
+}
+
+// Writes the record as a JSON object with string values on a single line,
+// so a batch becomes JSON Lines.
+pub fn write_json<W: Write>(out: &mut W, fields: &[(String, String)]) -> io::Result<()> {
+    let pairs: Vec<String> = fields
+        .iter()
+        .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+        .collect();
+    writeln!(out, "{{{}}}", pairs.join(","))
+}
+
+// Byte offset into the JSON text where parsing failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseJsonError(pub usize);
+
+impl fmt::Display for ParseJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid JSON record at byte {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseJsonError {}
+
+// Parses a record written by `write_json`: a flat object with string values.
+// This is not a general JSON parser, it rejects numbers, nesting and so on.
+pub fn parse_json(string: &str) -> Result<Fields, ParseJsonError> {
+    let mut parser = JsonParser { string, pos: 0 };
+    let mut fields = Fields::new();
+    parser.expect('{')?;
+    if !parser.eat('}') {
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            let value = parser.string()?;
+            fields.push((key, value));
+            if parser.eat('}') {
+                break;
+            }
+            parser.expect(',')?;
+        }
+    }
+    parser.skip_whitespace();
+    if parser.pos != string.len() {
+        return Err(ParseJsonError(parser.pos));
+    }
+    Ok(fields)
+}
+
+struct JsonParser<'a> {
+    string: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.string[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, chr: char) -> bool {
+        self.skip_whitespace();
+        if self.string[self.pos..].starts_with(chr) {
+            self.pos += chr.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, chr: char) -> Result<(), ParseJsonError> {
+        if self.eat(chr) { Ok(()) } else { Err(ParseJsonError(self.pos)) }
+    }
+
+    fn string(&mut self) -> Result<String, ParseJsonError> {
+        self.expect('"')?;
+        let mut result = String::new();
+        let mut chars = self.string[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            let error = ParseJsonError(self.pos + i);
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(result);
+                }
+                '\\' => {
+                    let escaped = match chars.next().ok_or_else(|| error.clone())?.1 {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            // Exactly 4 hex digits, from_str_radix alone would allow a sign
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                                return Err(error);
+                            }
+                            let code = u32::from_str_radix(&hex, 16).map_err(|_| error.clone())?;
+                            std::char::from_u32(code).ok_or(error)?
+                        }
+                        _ => return Err(error),
+                    };
+                    result.push(escaped);
+                }
+                c if (c as u32) < 0x20 => return Err(error),
+                c => result.push(c),
+            }
+        }
+        Err(ParseJsonError(self.string.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ordered_fields, parse_json, redact, write_json, write_text, Fields};
+    use crate::{update_hashmap, Passport};
+    use std::collections::HashMap;
+
+    const VALID: [&str; 3] = [
+        "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+        "eyr:2029 ecl:blu cid:129 byr:1989 iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm",
+        "hcl:#888785 hgt:164cm byr:2001 iyr:2015 cid:88 pid:545766238 ecl:hzl eyr:2022",
+    ];
+
+    fn parse_text(line: &str) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        update_hashmap(&mut map, line).unwrap();
+        map
+    }
+
+    fn to_map(fields: Fields) -> HashMap<String, String> {
+        fields.into_iter().collect()
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        for line in VALID.iter() {
+            let passport = Passport::from_hashmap(&parse_text(line)).unwrap();
+            let mut text = Vec::new();
+            write_text(&mut text, &passport.to_fields()).unwrap();
+            let text = String::from_utf8(text).unwrap();
+            let reparsed = Passport::from_hashmap(&parse_text(&text)).unwrap();
+            assert_eq!(passport, reparsed);
+            assert_eq!(parse_text(&text), parse_text(line));
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        for line in VALID.iter() {
+            let passport = Passport::from_hashmap(&parse_text(line)).unwrap();
+            let mut json = Vec::new();
+            write_json(&mut json, &passport.to_fields()).unwrap();
+            let json = String::from_utf8(json).unwrap();
+            let reparsed = Passport::from_hashmap(&to_map(parse_json(&json).unwrap())).unwrap();
+            assert_eq!(passport, reparsed);
+        }
+        let mut json = Vec::new();
+        write_json(&mut json, &ordered_fields(&parse_text(VALID[0]))).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"byr\":\"1980\",\"iyr\":\"2012\",\"eyr\":\"2030\",\"hgt\":\"74in\",\
+             \"hcl\":\"#623a2f\",\"ecl\":\"grn\",\"pid\":\"087499704\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        // Raw records keep invalid and unknown fields as they are
+        let map = parse_text("hgt:59cm zzz:\"a\\b\" cid:x iyr:2012");
+        let mut json = Vec::new();
+        write_json(&mut json, &ordered_fields(&map)).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json, "{\"iyr\":\"2012\",\"hgt\":\"59cm\",\"cid\":\"x\",\"zzz\":\"\\\"a\\\\b\\\"\"}\n");
+        assert_eq!(to_map(parse_json(&json).unwrap()), map);
+    }
+
+    #[test]
+    fn test_redact() {
+        let mut fields = ordered_fields(&parse_text(VALID[1]));
+        redact(&mut fields, &["cid".to_string()]);
+        let mut text = Vec::new();
+        write_text(&mut text, &fields).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "byr:1989 iyr:2014 eyr:2029 hgt:165cm hcl:#a97842 ecl:blu pid:896056539\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_json_errors() {
+        assert_eq!(parse_json("{}"), Ok(vec![]));
+        assert_eq!(parse_json(" { \"a\" : \"\\u00e9\" } "), Ok(vec![("a".to_string(), "\u{e9}".to_string())]));
+        assert!(parse_json("{\"a\":1}").is_err());
+        assert!(parse_json("{\"a\":\"b\",}").is_err());
+        assert!(parse_json("{\"a\":\"b\"} x").is_err());
+        assert!(parse_json("{\"a\":\"b").is_err());
+        assert!(parse_json("{\"a\":\"\\u+041\"}").is_err());
+        assert!(parse_json("{\"a\":\"\\u41\"}").is_err());
+        assert!(parse_json("{\"a\":\"\\u0041\"}").is_ok());
+    }
+}
@@ -1,58 +1,105 @@
+use day5::map::SeatMap;
+use day5::seat::{find_your_seat, Layout, Seat};
 use std::fs::File;
-use std::io::{BufReader, BufRead};
+use std::io::{BufRead, BufReader};
+use std::process::exit;
 
+const USAGE: &str = "Usage: day5 [--row-bits N] [--col-bits N] [--map] [FILE]\n       day5 [--row-bits N] [--col-bits N] --encode ID|ROW,COL";
+
+// The layout defaults to the puzzle's 7 row bits and 3 column bits.
+// With --map, the plane is printed with the taken seats, followed by every
+// free seat with taken seats on both sides, instead of only your seat.
+// With --encode, the boarding pass of a seat is printed.
 fn main() {
-    println!("{}", get_your_id("input.txt"));
+    let mut path = None;
+    let mut row_bits = Layout::PUZZLE.row_bits();
+    let mut col_bits = Layout::PUZZLE.col_bits();
+    let mut showmap = false;
+    let mut encode = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--row-bits" => row_bits = parse_bits(args.next()),
+            "--col-bits" => col_bits = parse_bits(args.next()),
+            "--map" => showmap = true,
+            "--encode" => encode = Some(args.next().unwrap_or_else(|| usage())),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let path = path.unwrap_or_else(|| "input.txt".to_string());
+    let layout = Layout::new(row_bits, col_bits).unwrap_or_else(|| {
+        println!("At most 32 row and column bits in total");
+        exit(64)
+    });
+    if let Some(seat) = encode {
+        println!("{}", parse_seat_arg(&seat, layout));
+        return
+    }
+    let seats = read_seats(&path, layout);
+    if showmap {
+        let map = SeatMap::new(layout, &seats);
+        print!("{}", map);
+        for seat in map.free_between_taken() {
+            println!("Free: {} (row {}, column {}, ID {})", seat, seat.row(), seat.col(), seat.id());
+        }
+        return
+    }
+    match find_your_seat(&seats) {
+        Some(id) => println!("{}", id),
+        None => {
+            eprintln!("Could not find your seat");
+            exit(1)
+        }
+    }
 }
 
-struct Seat {
-    index: u8,
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(64)
 }
 
-impl Seat {
-    fn parse(string: &str) -> Option<Self>  {
-        let mut index: u16 = 0;
-        let bytes = string.as_bytes();
-        if bytes.len() != 10 { return None };
-        for pair in bytes.iter().enumerate() {
-            index = (index << 1) + match pair {
-                (0..=6, b'B') => 1,
-                (0..=6, b'F') => 0,
-                (7..=9, b'R') => 1,
-                (7..=9, b'L') => 0,
-                _ => return None
-            };
-        }
-        return Some(Seat(index))
-    }
+fn parse_bits(arg: Option<String>) -> u32 {
+    arg.and_then(|s| s.parse().ok()).unwrap_or_else(|| usage())
 }
 
+// Either an ID or a row and column separated by a comma
+fn parse_seat_arg(string: &str, layout: Layout) -> Seat {
+    let numbers: Vec<u32> = string.split(',').map(|s| s.trim().parse().unwrap_or_else(|_| usage())).collect();
+    let seat = match numbers.as_slice() {
+        [id] => Seat::from_id(*id, layout),
+        [row, col] => Seat::new(*row, *col, layout),
+        _ => usage(),
+    };
+    seat.unwrap_or_else(|| {
+        println!("No seat {} in a plane of {} rows of {} seats", string, layout.rows(), layout.cols());
+        exit(64)
+    })
+}
 
-fn get_your_id(path: &str) -> u16 {
+// Blank lines are skipped, anything else must be a boarding pass
+fn read_seats(path: &str, layout: Layout) -> Vec<Seat> {
+    let file = File::open(path).unwrap_or_else(|e| {
+        eprintln!("Failed to open {}: {}", path, e);
+        exit(66)
+    });
     let mut seats = Vec::new();
-    let linereader = BufReader::new(File::open(path).expect("Failed to open file")).lines();
-    for (lineno, line) in linereader.enumerate()
-        .map(|(n, e)| (n, e.expect("Failed to read line.")))
-        .filter(|(_n, e)| (!e.trim().is_empty())) {
-        match partition_to_number(&line) {
-            Some(n) => seats.push(n),
-            None => panic!(format!("Failed to parse seat at line {}", lineno))
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.unwrap_or_else(|e| {
+            eprintln!("Failed to read line {}: {}", i + 1, e);
+            exit(74)
+        });
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
         }
-    };
-    seats.sort();
-    let mut prev: u16 = *seats.first().unwrap();
-    for seat in seats {
-        if seat == prev + 2 {
-            return prev + 1
+        match Seat::parse(line, layout) {
+            Ok(seat) => seats.push(seat),
+            Err(e) => {
+                eprintln!("Line {}: {}", i + 1, e);
+                exit(65)
+            }
         }
-        prev = seat
-    };
-    panic!("Could not find your seat")
-}
-
-#[test]
-fn test_parse() {
-    let input = "FBFBBFFRLR";
-    let seat = Seat::parse(input);
-    assert_eq!(seat, Seat { row: 44, col: 5 });
+    }
+    seats
 }
@@ -0,0 +1,105 @@
+use crate::seat::{Layout, Seat};
+use std::collections::BTreeSet;
+use std::fmt;
+
+// Which seats of a plane are taken. Only the taken seats are stored, since
+// a layout can have up to 2^32 seats.
+#[derive(Debug, Clone)]
+pub struct SeatMap {
+    layout: Layout,
+    taken: BTreeSet<u32>,
+}
+
+impl SeatMap {
+    // Seats of other layouts are read by their ID in this layout
+    pub fn new(layout: Layout, seats: &[Seat]) -> SeatMap {
+        SeatMap { layout, taken: seats.iter().map(|s| s.id()).collect() }
+    }
+
+    pub fn is_taken(&self, id: u32) -> bool {
+        self.taken.contains(&id)
+    }
+
+    // First and last row with a taken seat. The rows before and after are
+    // missing from the plane.
+    pub fn occupied_rows(&self) -> Option<(u32, u32)> {
+        let row = |id: &u32| Seat::from_id(*id, self.layout).map(|s| s.row());
+        Some((row(self.taken.iter().next()?)?, row(self.taken.iter().next_back()?)?))
+    }
+
+    // Every free seat whose neighbours on both sides are taken. Neighbours
+    // are seats with an ID one lower or higher, even across rows.
+    pub fn free_between_taken(&self) -> Vec<Seat> {
+        self.taken
+            .iter()
+            .filter(|&&id| id <= u32::MAX - 2 && !self.is_taken(id + 1) && self.is_taken(id + 2))
+            .filter_map(|&id| Seat::from_id(id + 1, self.layout))
+            .collect()
+    }
+}
+
+fn write_missing(f: &mut fmt::Formatter, first: u32, last: u32) -> fmt::Result {
+    if first == last {
+        writeln!(f, "row {}: missing", first)
+    } else {
+        writeln!(f, "rows {}-{}: missing", first, last)
+    }
+}
+
+// One line per row, with # for a taken seat and . for a free one, e.g.
+//  44 ##.#####
+// The missing rows at the front and back are summarized on one line each.
+impl fmt::Display for SeatMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lastrow = (self.layout.rows() - 1) as u32;
+        let (first, last) = match self.occupied_rows() {
+            Some(rows) => rows,
+            None => return write_missing(f, 0, lastrow),
+        };
+        let width = lastrow.to_string().len();
+        if first > 0 {
+            write_missing(f, 0, first - 1)?;
+        }
+        for row in first..=last {
+            write!(f, "{:>width$} ", row, width = width)?;
+            for col in 0..self.layout.cols() {
+                let taken = Seat::new(row, col as u32, self.layout).is_some_and(|s| self.is_taken(s.id()));
+                write!(f, "{}", if taken { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        if last < lastrow {
+            write_missing(f, last + 1, lastrow)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeatMap;
+    use crate::seat::{Layout, Seat};
+
+    fn seats(layout: Layout, ids: &[u32]) -> Vec<Seat> {
+        ids.iter().map(|&id| Seat::from_id(id, layout).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_map() {
+        let layout = Layout::new(3, 2).unwrap();
+        let map = SeatMap::new(layout, &seats(layout, &[5, 6, 8, 10, 11, 13, 15]));
+        assert_eq!(map.occupied_rows(), Some((1, 3)));
+        let free: Vec<u32> = map.free_between_taken().iter().map(|s| s.id()).collect();
+        assert_eq!(free, vec![7, 9, 12, 14]);
+        assert_eq!(map.to_string(), "row 0: missing\n1 .##.\n2 #.##\n3 .#.#\nrows 4-7: missing\n");
+    }
+
+    #[test]
+    fn test_empty_map() {
+        let layout = Layout::new(1, 1).unwrap();
+        let map = SeatMap::new(layout, &[]);
+        assert_eq!(map.occupied_rows(), None);
+        assert!(map.free_between_taken().is_empty());
+        assert_eq!(map.to_string(), "rows 0-1: missing\n");
+    }
+}
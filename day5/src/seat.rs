@@ -0,0 +1,229 @@
+use std::fmt;
+
+// How many bits of a boarding pass give the row, and how many the column.
+// The rows are encoded with F and B, then the columns with L and R.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Layout {
+    row_bits: u32,
+    col_bits: u32,
+}
+
+impl Layout {
+    // The plane of the puzzle: 128 rows of 8 seats
+    pub const PUZZLE: Layout = Layout { row_bits: 7, col_bits: 3 };
+
+    // Seat IDs must fit in a u32, so there can be at most 32 bits in total
+    pub fn new(row_bits: u32, col_bits: u32) -> Option<Layout> {
+        if row_bits.checked_add(col_bits)? > 32 {
+            return None
+        }
+        Some(Layout { row_bits, col_bits })
+    }
+
+    pub fn row_bits(&self) -> u32 {
+        self.row_bits
+    }
+
+    pub fn col_bits(&self) -> u32 {
+        self.col_bits
+    }
+
+    pub fn rows(&self) -> u64 {
+        1 << self.row_bits
+    }
+
+    pub fn cols(&self) -> u64 {
+        1 << self.col_bits
+    }
+
+    // Length of a boarding pass
+    pub fn pass_len(&self) -> usize {
+        (self.row_bits + self.col_bits) as usize
+    }
+}
+
+// A seat is stored as its ID, which is the boarding pass read as a binary
+// number: the row times the number of columns, plus the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Seat {
+    id: u32,
+    layout: Layout,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSeatError {
+    WrongLength { expected: usize, found: usize },
+    // Position is 1-based, and counts characters
+    InvalidChar { position: usize, found: char, expected: &'static str },
+}
+
+impl fmt::Display for ParseSeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseSeatError::WrongLength { expected, found } => {
+                write!(f, "expected {} characters, found {}", expected, found)
+            }
+            ParseSeatError::InvalidChar { position, found, expected } => {
+                write!(f, "character {}: expected {}, found '{}'", position, expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseSeatError {}
+
+impl Seat {
+    // None if the row or column does not exist in the layout
+    pub fn new(row: u32, col: u32, layout: Layout) -> Option<Seat> {
+        if row as u64 >= layout.rows() || col as u64 >= layout.cols() {
+            return None
+        }
+        let id = ((row as u64) << layout.col_bits) as u32 | col;
+        Some(Seat { id, layout })
+    }
+
+    // None if the ID is too large for the layout
+    pub fn from_id(id: u32, layout: Layout) -> Option<Seat> {
+        if id as u64 >= layout.rows() * layout.cols() {
+            return None
+        }
+        Some(Seat { id, layout })
+    }
+
+    // Parses a boarding pass like "FBFBBFFRLR". Surrounding whitespace is
+    // not allowed.
+    pub fn parse(string: &str, layout: Layout) -> Result<Seat, ParseSeatError> {
+        let found = string.chars().count();
+        if found != layout.pass_len() {
+            return Err(ParseSeatError::WrongLength { expected: layout.pass_len(), found });
+        }
+        let mut id: u64 = 0;
+        for (i, c) in string.chars().enumerate() {
+            let isrow = i < layout.row_bits as usize;
+            let bit = match (isrow, c) {
+                (true, 'F') | (false, 'L') => 0,
+                (true, 'B') | (false, 'R') => 1,
+                _ => {
+                    let expected = if isrow { "F or B" } else { "L or R" };
+                    return Err(ParseSeatError::InvalidChar { position: i + 1, found: c, expected });
+                }
+            };
+            id = (id << 1) | bit;
+        }
+        Ok(Seat { id: id as u32, layout })
+    }
+
+    pub fn row(&self) -> u32 {
+        ((self.id as u64) >> self.layout.col_bits) as u32
+    }
+
+    pub fn col(&self) -> u32 {
+        ((self.id as u64) & ((1 << self.layout.col_bits) - 1)) as u32
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+// Prints the boarding pass, so parsing the output gives the same seat
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in (0..self.layout.pass_len()).rev() {
+            let bit = (self.id as u64 >> i) & 1 == 1;
+            let isrow = i >= self.layout.col_bits as usize;
+            let c = match (isrow, bit) {
+                (true, false) => 'F',
+                (true, true) => 'B',
+                (false, false) => 'L',
+                (false, true) => 'R',
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+// Your seat is the only free one whose neighbouring IDs are both taken.
+// Returns None if there is no such seat, or more than one.
+pub fn find_your_seat(seats: &[Seat]) -> Option<u32> {
+    let mut ids: Vec<u32> = seats.iter().map(|s| s.id()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut gaps = ids.windows(2).filter(|w| w[1] == w[0] + 2).map(|w| w[0] + 1);
+    match (gaps.next(), gaps.next()) {
+        (Some(id), None) => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_your_seat, Layout, ParseSeatError, Seat};
+
+    #[test]
+    fn test_parse() {
+        let seat = Seat::parse("FBFBBFFRLR", Layout::PUZZLE).unwrap();
+        assert_eq!((seat.row(), seat.col(), seat.id()), (44, 5, 357));
+        let seat = Seat::parse("BBFFBBFRLL", Layout::PUZZLE).unwrap();
+        assert_eq!((seat.row(), seat.col(), seat.id()), (102, 4, 820));
+        assert_eq!(Seat::new(102, 4, Layout::PUZZLE), Some(seat));
+        assert_eq!(Seat::from_id(820, Layout::PUZZLE), Some(seat));
+    }
+
+    #[test]
+    fn test_layouts() {
+        let wide = Layout::new(5, 4).unwrap();
+        let seat = Seat::parse("BFFBFRRLR", wide).unwrap();
+        assert_eq!((seat.row(), seat.col(), seat.id()), (18, 13, 18 * 16 + 13));
+        let seat = Seat::parse("LRL", Layout::new(0, 3).unwrap()).unwrap();
+        assert_eq!((seat.row(), seat.col()), (0, 2));
+        let huge = Layout::new(16, 16).unwrap();
+        assert_eq!(Seat::new(65535, 65535, huge).unwrap().id(), u32::MAX);
+        assert!(Layout::new(20, 13).is_none());
+        assert!(Layout::new(u32::MAX, 1).is_none());
+        assert!(Seat::new(128, 0, Layout::PUZZLE).is_none());
+        assert!(Seat::from_id(1024, Layout::PUZZLE).is_none());
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(Seat::from_id(357, Layout::PUZZLE).unwrap().to_string(), "FBFBBFFRLR");
+        assert_eq!(Seat::new(102, 4, Layout::PUZZLE).unwrap().to_string(), "BBFFBBFRLL");
+        let wide = Layout::new(5, 4).unwrap();
+        for id in [0, 1, 300, 511].iter() {
+            let pass = Seat::from_id(*id, wide).unwrap().to_string();
+            assert_eq!(Seat::parse(&pass, wide).unwrap().id(), *id);
+        }
+        assert_eq!(Seat::new(0, 0, Layout::new(0, 0).unwrap()).unwrap().to_string(), "");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Seat::parse("FBFBBFFRL", Layout::PUZZLE),
+            Err(ParseSeatError::WrongLength { expected: 10, found: 9 })
+        );
+        assert_eq!(
+            Seat::parse("FBFBBFLRLR", Layout::PUZZLE),
+            Err(ParseSeatError::InvalidChar { position: 7, found: 'L', expected: "F or B" })
+        );
+        assert_eq!(
+            Seat::parse("FBFBBFFRBR", Layout::PUZZLE),
+            Err(ParseSeatError::InvalidChar { position: 9, found: 'B', expected: "L or R" })
+        );
+    }
+
+    #[test]
+    fn test_find_your_seat() {
+        let seats: Vec<Seat> = [3, 4, 6, 7].iter().map(|&id| Seat::from_id(id, Layout::PUZZLE).unwrap()).collect();
+        assert_eq!(find_your_seat(&seats), Some(5));
+        assert_eq!(find_your_seat(&seats[..2]), None);
+        let seats: Vec<Seat> = [1, 3, 5].iter().map(|&id| Seat::from_id(id, Layout::PUZZLE).unwrap()).collect();
+        assert_eq!(find_your_seat(&seats), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+use std::str::FromStr;
+
+// Alphabets of at most this many characters store answers as bits
+const MAX_BITS: usize = 128;
+
+// The characters that are valid answers. Each answer set belongs to an
+// alphabet, and sets of different alphabets must not be combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    // Sorted and without duplicates. In small alphabets, the position of a
+    // character is its bit.
+    chars: Vec<char>,
+}
+
+// The answers of one person or one group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnswerSet {
+    Bits(u128),
+    Chars(HashSet<char>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAnswer(pub char);
+
+impl fmt::Display for UnknownAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Answer '{}' is not in the alphabet", self.0)
+    }
+}
+
+impl std::error::Error for UnknownAnswer {}
+
+impl Alphabet {
+    pub fn new<I: IntoIterator<Item = char>>(chars: I) -> Alphabet {
+        let mut chars: Vec<char> = chars.into_iter().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        Alphabet { chars }
+    }
+
+    // The alphabet of the puzzle
+    pub fn lowercase() -> Alphabet {
+        Alphabet::new('a'..='z')
+    }
+
+    // Every character in the text except whitespace
+    pub fn infer(text: &str) -> Alphabet {
+        Alphabet::new(text.chars().filter(|c| !c.is_whitespace()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    fn is_small(&self) -> bool {
+        self.chars.len() <= MAX_BITS
+    }
+
+    pub fn empty(&self) -> AnswerSet {
+        if self.is_small() {
+            AnswerSet::Bits(0)
+        } else {
+            AnswerSet::Chars(HashSet::new())
+        }
+    }
+
+    // The set of every character, as the starting point for intersections
+    pub fn full(&self) -> AnswerSet {
+        if self.is_small() {
+            AnswerSet::Bits(u128::MAX.checked_shr((MAX_BITS - self.chars.len()) as u32).unwrap_or(0))
+        } else {
+            AnswerSet::Chars(self.chars.iter().copied().collect())
+        }
+    }
+
+    // The answers on one line, each character being one answer
+    pub fn parse(&self, line: &str) -> Result<AnswerSet, UnknownAnswer> {
+        let mut set = self.empty();
+        for chr in line.chars() {
+            let index = self.chars.binary_search(&chr).map_err(|_| UnknownAnswer(chr))?;
+            match set {
+                AnswerSet::Bits(ref mut bits) => *bits |= 1 << index,
+                AnswerSet::Chars(ref mut chars) => {
+                    chars.insert(chr);
+                }
+            }
+        }
+        Ok(set)
+    }
+
+    // The answers in the set, in alphabet order
+    pub fn chars(&self, set: &AnswerSet) -> Vec<char> {
+        match set {
+            AnswerSet::Bits(bits) => self
+                .chars
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| bits >> i & 1 == 1)
+                .map(|(_, c)| *c)
+                .collect(),
+            AnswerSet::Chars(chars) => self.chars.iter().filter(|c| chars.contains(c)).copied().collect(),
+        }
+    }
+}
+
+// A list of characters and ranges like "a-z", e.g. "a-zA-Z0-9" or "αβγ".
+// A dash at the start or end stands for itself.
+impl FromStr for Alphabet {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = string.chars().collect();
+        let mut alphabet = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if i + 2 < chars.len() && chars[i + 1] == '-' {
+                let (low, high) = (chars[i], chars[i + 2]);
+                if low > high {
+                    return Err(format!("Invalid range {}-{} in alphabet", low, high));
+                }
+                alphabet.extend(low..=high);
+                i += 3;
+            } else {
+                alphabet.push(chars[i]);
+                i += 1;
+            }
+        }
+        if alphabet.is_empty() {
+            return Err("Empty alphabet".to_string());
+        }
+        Ok(Alphabet::new(alphabet))
+    }
+}
+
+impl AnswerSet {
+    pub fn len(&self) -> usize {
+        match self {
+            AnswerSet::Bits(bits) => bits.count_ones() as usize,
+            AnswerSet::Chars(chars) => chars.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Panics if the sets are of different alphabets with different representations
+impl BitOr for AnswerSet {
+    type Output = AnswerSet;
+
+    fn bitor(self, other: AnswerSet) -> AnswerSet {
+        match (self, other) {
+            (AnswerSet::Bits(a), AnswerSet::Bits(b)) => AnswerSet::Bits(a | b),
+            (AnswerSet::Chars(mut a), AnswerSet::Chars(b)) => {
+                a.extend(b);
+                AnswerSet::Chars(a)
+            }
+            _ => panic!("Cannot combine answer sets of different alphabets"),
+        }
+    }
+}
+
+impl BitAnd for AnswerSet {
+    type Output = AnswerSet;
+
+    fn bitand(self, other: AnswerSet) -> AnswerSet {
+        match (self, other) {
+            (AnswerSet::Bits(a), AnswerSet::Bits(b)) => AnswerSet::Bits(a & b),
+            (AnswerSet::Chars(mut a), AnswerSet::Chars(b)) => {
+                a.retain(|c| b.contains(c));
+                AnswerSet::Chars(a)
+            }
+            _ => panic!("Cannot combine answer sets of different alphabets"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alphabet, AnswerSet, UnknownAnswer};
+
+    #[test]
+    fn test_lowercase() {
+        let alphabet = Alphabet::lowercase();
+        let group = ["abc", "acd", "ace"];
+        let sets: Vec<AnswerSet> = group.iter().map(|l| alphabet.parse(l).unwrap()).collect();
+        let any = sets.iter().cloned().fold(alphabet.empty(), |a, b| a | b);
+        let all = sets.iter().cloned().fold(alphabet.full(), |a, b| a & b);
+        assert_eq!(alphabet.chars(&any), vec!['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(alphabet.chars(&all), vec!['a', 'c']);
+        assert_eq!(alphabet.full().len(), 26);
+        assert_eq!(alphabet.parse("aB"), Err(UnknownAnswer('B')));
+    }
+
+    #[test]
+    fn test_spec() {
+        let alphabet: Alphabet = "a-zA-Z0-9".parse().unwrap();
+        assert_eq!(alphabet.len(), 62);
+        assert_eq!(alphabet.chars(&alphabet.parse("Zz9").unwrap()), vec!['9', 'Z', 'z']);
+        assert_eq!("-a-c-".parse::<Alphabet>().unwrap().len(), 4);
+        assert!("z-a".parse::<Alphabet>().is_err());
+        assert_eq!("αβγ".parse::<Alphabet>().unwrap().parse("γα").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_large_alphabet() {
+        let alphabet = Alphabet::new('\u{4e00}'..'\u{4f00}');
+        assert!(matches!(alphabet.empty(), AnswerSet::Chars(_)));
+        let a = alphabet.parse("\u{4e00}\u{4e01}\u{4eff}").unwrap();
+        let b = alphabet.parse("\u{4e01}\u{4eff}\u{4e02}").unwrap();
+        assert_eq!((a.clone() | b.clone()).len(), 4);
+        assert_eq!(alphabet.chars(&(alphabet.full() & a & b)), vec!['\u{4e01}', '\u{4eff}']);
+        assert_eq!(alphabet.full().len(), 256);
+    }
+
+    #[test]
+    fn test_full_bits() {
+        assert_eq!(Alphabet::new(std::iter::empty()).full(), AnswerSet::Bits(0));
+        let alphabet = Alphabet::new((0..128u8).map(char::from));
+        assert_eq!(alphabet.full(), AnswerSet::Bits(u128::MAX));
+    }
+}
@@ -1,77 +1,87 @@
-use std::fs::File;
-use std::io::{BufReader, BufRead};
+use day6::answers::{Alphabet, AnswerSet};
+use day6::stats::{Survey, Tally};
+use records::records;
 use std::process::exit;
 
-fn main() {
-    let path = std::env::args().nth(1).unwrap_or_else(|| {println!("Usage: day6 [FILE]"); exit(1)});
-    partx(path.trim(), 0, &|a, b| a | b);
-    partx(path.trim(), u32::MAX, &|a, b| a & b);
-}
-
-struct LineSepIterator<T: BufRead> {
-    io: T,
-    linebuf: String
-}
+const USAGE: &str = "Usage: day6 [--alphabet CHARS] [--quorum K]... [--report] [FILE]";
 
-impl <T: BufRead> Iterator for LineSepIterator<T> {
-    type Item = Result<Vec<String>, std::io::Error>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.linebuf.clear();
-        loop {
-            let nread_res = self.io.read_line(&mut self.linebuf);
-            let nread = match nread_res {
-                Err(e) => return Some(Err(e)),
-                Ok(o) => o
-            };
-            if nread > 1 {
-                continue;
-            }
-            if !self.linebuf.is_empty() {
-                return Some(Ok(self.linebuf.trim().split('\n').map(|x| x.to_string()).collect()))
+// Each character on a line is one answer. Unless an alphabet is given, like
+// "a-z" or "a-zA-Z0-9", every character in the file is a possible answer.
+// With --quorum, also counts the answers given by at least K people of a
+// group, where K is at least 1. With --report, prints statistics of the
+// groups and questions.
+fn main() {
+    let mut path = None;
+    let mut alphabet = None;
+    let mut quorums: Vec<usize> = Vec::new();
+    let mut report = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--alphabet" => {
+                let spec = args.next().unwrap_or_else(|| usage());
+                alphabet = Some(spec.parse::<Alphabet>().unwrap_or_else(|e| {println!("{}", e); exit(64)}))
             }
-            if nread == 0 {
-                return None
-            } else if nread == 1 {
-                self.linebuf.clear();
+            "--quorum" => {
+                quorums.push(args.next().and_then(|k| k.parse().ok()).filter(|&k| k > 0).unwrap_or_else(|| usage()))
             }
+            "--report" => report = true,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
         }
     }
-}
+    let path = path.unwrap_or_else(|| usage());
+    let text = std::fs::read_to_string(path.trim()).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", path, e);
+        exit(66)
+    });
+    let alphabet = alphabet.unwrap_or_else(|| Alphabet::infer(&text));
+    let groups = parse_groups(&text, &alphabet);
+    partx(&groups, alphabet.empty(), &|a, b| a | b);
+    partx(&groups, alphabet.full(), &|a, b| a & b);
 
-impl<T: BufRead> LineSepIterator<T> {
-    fn new(io: T) -> LineSepIterator<T> {
-        LineSepIterator{io: io, linebuf: String::new()}
+    let tallies: Vec<Tally> = groups
+        .iter()
+        .map(|group| group.iter().fold(Tally::default(), |tally, set| tally.add(&alphabet, set)))
+        .collect();
+    for k in quorums {
+        let n: usize = tallies.iter().map(|tally| tally.at_least(k).len()).sum();
+        println!("At least {}: {}", k, n);
     }
-}
-
-impl LineSepIterator<BufReader<File>> {
-    fn from_path(path: &str) -> Result<LineSepIterator<BufReader<File>>, std::io::Error> {
-        let file = File::open(path)?;   
-        Ok(LineSepIterator::new(BufReader::new(file)))
+    if report {
+        let mut survey = Survey::default();
+        for tally in tallies.iter() {
+            survey.add(tally);
+        }
+        print!("{}", survey);
     }
 }
 
-fn partx<F: std::ops::Fn(u32, u32) -> u32>(path: &str, init: u32, op: &F) -> () {
-    let reader = LineSepIterator::from_path(path)
-    .unwrap_or_else(|_| {println!("Could not open file"); exit(1)});
-    let n: u32 = reader.map(|chunks| {
-        chunks.unwrap_or_else(|_| {println!("Could not read line"); exit(1)})
-        .iter().map(|line| {
-            parse_bitset(line)
-        }).fold(init, op).count_ones()
-    }).sum();
-    println!("{}", n);
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(64)
 }
 
-fn parse_bitset(string: &str) -> u32 {
-    let mut result: u32 = 0;
-    for chr in string.chars() {
-        let shift = match chr {
-            'a'..='z' => (chr as u32) - ('a' as u32) ,
-            _ => panic!(format!("Unrecognized char: {}", chr))
-        };
-        result |= 1u32 << shift;
+// Groups are separated by blank lines
+fn parse_groups(text: &str, alphabet: &Alphabet) -> Vec<Vec<AnswerSet>> {
+    let mut groups = Vec::new();
+    for group in records(text) {
+        let sets = group.lines().enumerate().map(|(i, line)| {
+            alphabet.parse(line.trim()).unwrap_or_else(|e| {
+                eprintln!("Line {}: {}", group.first + i, e);
+                exit(65)
+            })
+        });
+        groups.push(sets.collect());
     }
-    return result
+    groups
+}
+
+// Answers are folded with `op` per group, starting from `init`
+fn partx<F: Fn(AnswerSet, AnswerSet) -> AnswerSet>(groups: &[Vec<AnswerSet>], init: AnswerSet, op: &F) {
+    let n: usize = groups
+        .iter()
+        .map(|group| group.iter().cloned().fold(init.clone(), op).len())
+        .sum();
+    println!("{}", n);
 }
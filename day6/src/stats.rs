@@ -0,0 +1,156 @@
+use crate::answers::{Alphabet, AnswerSet};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// How many people of one group gave each answer. A tally is built by
+// folding the answer sets of the group into it, like the sets themselves
+// are folded with | or &:
+//     sets.fold(Tally::default(), |tally, set| tally.add(&alphabet, &set))
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tally {
+    size: usize,
+    counts: BTreeMap<char, usize>,
+}
+
+impl Tally {
+    pub fn add(mut self, alphabet: &Alphabet, set: &AnswerSet) -> Tally {
+        self.size += 1;
+        for chr in alphabet.chars(set) {
+            *self.counts.entry(chr).or_insert(0) += 1;
+        }
+        self
+    }
+
+    // Number of people in the group
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn count(&self, answer: char) -> usize {
+        self.counts.get(&answer).copied().unwrap_or(0)
+    }
+
+    // The answers given by at least k people. With k = 1 this is the union
+    // of the group's answers, and with k = size() the intersection. Only
+    // answers someone gave are counted, so k = 0 is the same as k = 1.
+    pub fn at_least(&self, k: usize) -> Vec<char> {
+        self.counts.iter().filter(|(_, &n)| n >= k).map(|(&c, _)| c).collect()
+    }
+
+    // The answers everyone in the group gave. Empty for an empty group.
+    pub fn unanimous(&self) -> Vec<char> {
+        if self.size == 0 {
+            return Vec::new()
+        }
+        self.at_least(self.size)
+    }
+}
+
+// Totals of one question over all groups
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuestionStats {
+    // People who answered it
+    pub people: usize,
+    // Groups where anyone answered it
+    pub groups: usize,
+    // Groups where everyone answered it
+    pub unanimous: usize,
+}
+
+// Statistics over all groups of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Survey {
+    pub groups: usize,
+    pub people: usize,
+    // Number of groups of each size
+    pub sizes: BTreeMap<usize, usize>,
+    pub questions: BTreeMap<char, QuestionStats>,
+}
+
+impl Survey {
+    pub fn add(&mut self, tally: &Tally) {
+        self.groups += 1;
+        self.people += tally.size();
+        *self.sizes.entry(tally.size()).or_insert(0) += 1;
+        for (&chr, &n) in tally.counts.iter() {
+            let stats = self.questions.entry(chr).or_default();
+            stats.people += n;
+            stats.groups += 1;
+            if n == tally.size() {
+                stats.unanimous += 1;
+            }
+        }
+    }
+
+    // Questions answered unanimously by at least one group, most often first
+    pub fn most_unanimous(&self) -> Vec<(char, usize)> {
+        let mut questions: Vec<(char, usize)> = self
+            .questions
+            .iter()
+            .filter(|(_, s)| s.unanimous > 0)
+            .map(|(&c, s)| (c, s.unanimous))
+            .collect();
+        questions.sort_by(|(a, na), (b, nb)| nb.cmp(na).then(a.cmp(b)));
+        questions
+    }
+}
+
+impl fmt::Display for Survey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} groups, {} people", self.groups, self.people)?;
+        writeln!(f)?;
+        writeln!(f, "size  groups")?;
+        for (size, n) in self.sizes.iter() {
+            writeln!(f, "{:>4}  {:>6}", size, n)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "question  people  groups  unanimous")?;
+        for (chr, s) in self.questions.iter() {
+            writeln!(f, "{:>8}  {:>6}  {:>6}  {:>9}", chr, s.people, s.groups, s.unanimous)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Most often unanimous:")?;
+        for (chr, n) in self.most_unanimous() {
+            writeln!(f, "{} in {} of {} groups", chr, n, self.questions[&chr].groups)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuestionStats, Survey, Tally};
+    use crate::answers::Alphabet;
+
+    fn tally(alphabet: &Alphabet, lines: &[&str]) -> Tally {
+        lines
+            .iter()
+            .map(|l| alphabet.parse(l).unwrap())
+            .fold(Tally::default(), |tally, set| tally.add(alphabet, &set))
+    }
+
+    #[test]
+    fn test_tally() {
+        let alphabet = Alphabet::lowercase();
+        let group = tally(&alphabet, &["abc", "ab", "bd", "b"]);
+        assert_eq!(group.size(), 4);
+        assert_eq!((group.count('b'), group.count('z')), (4, 0));
+        assert_eq!(group.at_least(1), vec!['a', 'b', 'c', 'd']);
+        assert_eq!(group.at_least(2), vec!['a', 'b']);
+        assert_eq!(group.unanimous(), vec!['b']);
+        assert!(Tally::default().unanimous().is_empty());
+    }
+
+    #[test]
+    fn test_survey() {
+        let alphabet = Alphabet::lowercase();
+        let mut survey = Survey::default();
+        for group in [&["abc"][..], &["a", "b", "c"], &["ab", "ac"], &["a", "a", "a", "a"], &["b"]].iter() {
+            survey.add(&tally(&alphabet, group));
+        }
+        assert_eq!((survey.groups, survey.people), (5, 11));
+        assert_eq!(survey.sizes.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(), vec![(1, 2), (2, 1), (3, 1), (4, 1)]);
+        assert_eq!(survey.questions[&'a'], QuestionStats { people: 8, groups: 4, unanimous: 3 });
+        assert_eq!(survey.most_unanimous(), vec![('a', 3), ('b', 2), ('c', 1)]);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::Bag;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    // No rule mentions the bag
+    UnknownBag(String),
+    // The bag is inside other bags, but there is no rule for what it contains
+    MissingRule(String),
+    DuplicateRule(String),
+    // Bags that end up inside themselves, starting and ending with the same bag
+    Cycle(Vec<String>),
+    // The bag contains more than u64::MAX bags
+    Overflow(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::UnknownBag(name) => write!(f, "No rule mentions {} bags", name),
+            GraphError::MissingRule(name) => write!(f, "No rule for what {} bags contain", name),
+            GraphError::DuplicateRule(name) => write!(f, "More than one rule for {} bags", name),
+            GraphError::Cycle(path) => write!(f, "Bags contain themselves: {}", path.join(" -> ")),
+            GraphError::Overflow(name) => write!(f, "Too many bags inside {} bags", name),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+// The rules as a graph with an edge from each bag to the bags directly
+// inside it, and the same edges reversed. Bags are numbered in the order
+// they are first mentioned.
+#[derive(Debug, Clone)]
+pub struct BagGraph {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    // How many of which bags are inside each bag, or None if it has no rule
+    inner: Vec<Option<Vec<(usize, usize)>>>,
+    // The bags each bag is directly inside of
+    outer: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    OnPath,
+    Done,
+}
+
+impl BagGraph {
+    pub fn from_rules(rules: &[Bag]) -> Result<BagGraph, GraphError> {
+        let mut graph = BagGraph { names: Vec::new(), index: HashMap::new(), inner: Vec::new(), outer: Vec::new() };
+        for (outer, inners) in rules {
+            let node = graph.node_or_insert(outer);
+            if graph.inner[node].is_some() {
+                return Err(GraphError::DuplicateRule(outer.clone()));
+            }
+            let mut edges = Vec::with_capacity(inners.len());
+            for (n, inner) in inners {
+                let child = graph.node_or_insert(inner);
+                graph.outer[child].push(node);
+                edges.push((*n, child));
+            }
+            graph.inner[node] = Some(edges);
+        }
+        Ok(graph)
+    }
+
+    fn node_or_insert(&mut self, name: &str) -> usize {
+        if let Some(&node) = self.index.get(name) {
+            return node;
+        }
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), self.names.len() - 1);
+        self.inner.push(None);
+        self.outer.push(Vec::new());
+        self.names.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn node(&self, name: &str) -> Result<usize, GraphError> {
+        self.index.get(name).copied().ok_or_else(|| GraphError::UnknownBag(name.to_string()))
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    // The bags directly inside the bag, and how many of each
+    pub fn inner(&self, name: &str) -> Result<Vec<(usize, &str)>, GraphError> {
+        let edges = self.inner[self.node(name)?]
+            .as_ref()
+            .ok_or_else(|| GraphError::MissingRule(name.to_string()))?;
+        Ok(edges.iter().map(|&(n, child)| (n, self.name(child))).collect())
+    }
+
+    // The bags the bag is directly inside of
+    pub fn outer(&self, name: &str) -> Result<Vec<&str>, GraphError> {
+        Ok(self.outer[self.node(name)?].iter().map(|&node| self.name(node)).collect())
+    }
+
+    // Every bag the bag ends up inside of, directly or not
+    pub fn containers(&self, name: &str) -> Result<HashSet<&str>, GraphError> {
+        let mut unprocessed = vec![self.node(name)?];
+        let mut processed: HashSet<usize> = HashSet::new();
+        while let Some(node) = unprocessed.pop() {
+            for &outer in self.outer[node].iter() {
+                if processed.insert(outer) {
+                    unprocessed.push(outer);
+                }
+            }
+        }
+        Ok(processed.into_iter().map(|node| self.name(node)).collect())
+    }
+
+    // The total number of bags inside the bag. Fails if the bag ends up
+    // inside itself, or if a bag inside it has no rule.
+    pub fn count_inside(&self, name: &str) -> Result<u64, GraphError> {
+        let node = self.node(name)?;
+        if let Some(cycle) = self.cycle_from(&[node]) {
+            return Err(GraphError::Cycle(cycle.iter().map(|&n| self.name(n).to_string()).collect()));
+        }
+        self.count_memoized(node, &mut vec![None; self.len()])
+    }
+
+    fn count_memoized(&self, node: usize, counts: &mut Vec<Option<u64>>) -> Result<u64, GraphError> {
+        if let Some(n) = counts[node] {
+            return Ok(n);
+        }
+        let name = self.name(node);
+        let edges = self.inner[node].as_ref().ok_or_else(|| GraphError::MissingRule(name.to_string()))?;
+        let mut total: u64 = 0;
+        for &(n, child) in edges.iter() {
+            // Each of the n bags, and everything inside them
+            let inside = self.count_memoized(child, counts)?;
+            total = inside
+                .checked_add(1)
+                .and_then(|m| m.checked_mul(n as u64))
+                .and_then(|m| m.checked_add(total))
+                .ok_or_else(|| GraphError::Overflow(name.to_string()))?;
+        }
+        counts[node] = Some(total);
+        Ok(total)
+    }
+
+    // Some path of bags that ends up inside its first bag, like
+    // ["a", "b", "a"] if a contains b and b contains a
+    pub fn find_cycle(&self) -> Option<Vec<&str>> {
+        let all: Vec<usize> = (0..self.len()).collect();
+        let cycle = self.cycle_from(&all)?;
+        Some(cycle.iter().map(|&n| self.name(n)).collect())
+    }
+
+    // Depth-first search from each start, keeping the current path on a stack
+    fn cycle_from(&self, starts: &[usize]) -> Option<Vec<usize>> {
+        let mut visits = vec![Visit::New; self.len()];
+        for &start in starts {
+            if visits[start] != Visit::New {
+                continue;
+            }
+            visits[start] = Visit::OnPath;
+            // Each bag on the path, and the index of its next edge to follow
+            let mut path: Vec<(usize, usize)> = vec![(start, 0)];
+            while let Some(&(node, edge)) = path.last() {
+                let edges = self.inner[node].as_deref().unwrap_or(&[]);
+                let child = match edges.get(edge) {
+                    Some(&(_n, child)) => child,
+                    None => {
+                        visits[node] = Visit::Done;
+                        path.pop();
+                        continue;
+                    }
+                };
+                path.last_mut().unwrap().1 += 1;
+                match visits[child] {
+                    Visit::New => {
+                        visits[child] = Visit::OnPath;
+                        path.push((child, 0));
+                    }
+                    Visit::OnPath => {
+                        let first = path.iter().position(|&(n, _)| n == child).unwrap();
+                        let mut cycle: Vec<usize> = path[first..].iter().map(|&(n, _)| n).collect();
+                        cycle.push(child);
+                        return Some(cycle);
+                    }
+                    Visit::Done => (),
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BagGraph, GraphError};
+    use crate::parse_rule_file;
+
+    const EXAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.
+";
+
+    fn build(text: &str) -> BagGraph {
+        BagGraph::from_rules(&parse_rule_file(text.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_example() {
+        let graph = build(EXAMPLE);
+        assert_eq!(graph.len(), 9);
+        assert_eq!(graph.containers("shiny gold").unwrap().len(), 4);
+        assert!(graph.containers("light red").unwrap().is_empty());
+        assert_eq!(graph.count_inside("shiny gold").unwrap(), 32);
+        assert_eq!(graph.count_inside("faded blue").unwrap(), 0);
+        assert_eq!(graph.inner("bright white").unwrap(), vec![(1, "shiny gold")]);
+        assert_eq!(graph.outer("bright white").unwrap(), vec!["light red", "dark orange"]);
+        assert_eq!(graph.find_cycle(), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let graph = build(EXAMPLE);
+        assert_eq!(graph.containers("plaid red"), Err(GraphError::UnknownBag("plaid red".to_string())));
+        assert_eq!(graph.count_inside("plaid red"), Err(GraphError::UnknownBag("plaid red".to_string())));
+
+        let text = EXAMPLE.replace("dotted black bags contain no other bags.\n", "");
+        assert_eq!(
+            build(&text).count_inside("shiny gold"),
+            Err(GraphError::MissingRule("dotted black".to_string()))
+        );
+        let rules = parse_rule_file(format!("{}faded blue bags contain no other bags.\n", EXAMPLE).as_bytes()).unwrap();
+        assert_eq!(BagGraph::from_rules(&rules).unwrap_err(), GraphError::DuplicateRule("faded blue".to_string()));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let text = EXAMPLE.replace(
+            "dotted black bags contain no other bags.",
+            "dotted black bags contain 1 muted yellow bag.",
+        );
+        let graph = build(&text);
+        let cycle = vec!["shiny gold", "dark olive", "dotted black", "muted yellow", "shiny gold"];
+        assert_eq!(graph.find_cycle(), Some(cycle.clone()));
+        match graph.count_inside("muted yellow") {
+            Err(GraphError::Cycle(path)) => {
+                assert_eq!(path, vec!["muted yellow", "shiny gold", "dark olive", "dotted black", "muted yellow"])
+            }
+            other => panic!("Expected a cycle, got {:?}", other),
+        }
+        // Bags outside of the cycle can still be counted
+        assert_eq!(graph.count_inside("faded blue").unwrap(), 0);
+        assert_eq!(graph.containers("light red").unwrap().len(), 0);
+    }
+}
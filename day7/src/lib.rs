@@ -0,0 +1,82 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate regex;
+
+pub mod graph;
+
+use regex::Regex;
+use std::fmt;
+use std::io::BufRead;
+
+lazy_static! {
+    static ref HEAD_RE: Regex = Regex::new(r"^(\w+ \w+) bags $").unwrap();
+    static ref TAIL_RE: Regex =
+        Regex::new(r"^(?P<num>\d+) (?P<kind>\w+ \w+) bags?\.?$").unwrap();
+}
+
+// The 1-based line that could not be read or parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserError(pub usize);
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot parse rule at line {}", self.0)
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+pub type Bag = (String, Vec<(usize, String)>);
+
+// Input is of kind:
+// big red bags contain 1 dotted pink bag, 3 metallic orange bags.
+pub fn parse_rule(string: &str) -> Option<Bag> {
+    let mut headtail = string.split("contain ");
+
+    // Verify head (the thing before "contain ")
+    let match1 = HEAD_RE
+        .captures(headtail.next()?)?
+        .get(1)?
+        .as_str()
+        .to_string();
+    let tail = headtail.next()?;
+    if headtail.next().is_some() {
+        return None;
+    }
+
+    // Verify tail (the thing after "contain ")
+    let mut vec: Vec<(usize, String)> = Vec::new();
+    if tail == "no other bags." {
+        return Some((match1, vec));
+    }
+
+    // Fill in tail
+    for str in tail.split(", ") {
+        let caps = TAIL_RE.captures(str)?;
+        let n = caps.name("num")?.as_str().parse::<usize>().ok()?;
+        let kind = caps.name("kind")?.as_str().to_string();
+        vec.push((n, kind));
+    }
+    Some((match1, vec))
+}
+
+// Load in the file to a vector of (o, [(n, i) ... ]) where o is the outer bag
+// which must contain n of i inner bags etc.
+pub fn parse_rule_file<T: BufRead>(reader: T) -> Result<Vec<Bag>, ParserError> {
+    let mut result: Vec<Bag> = Vec::new();
+    for (lineno, lineres) in reader.lines().enumerate() {
+        let line = match lineres {
+            Ok(line) => line,
+            Err(_) => return Err(ParserError(lineno + 1)),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let elem = match parse_rule(line.trim()) {
+            Some(e) => e,
+            None => return Err(ParserError(lineno + 1)),
+        };
+        result.push(elem);
+    }
+    Ok(result)
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+// A rectangular grid stored row by row in a single vector. Lookups with `get`
+// can optionally wrap around horizontally and/or vertically, so the grid
+// behaves like it is tiled infinitely along those axes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    nrows: usize,
+    ncols: usize,
+    wrap_horizontal: bool,
+    wrap_vertical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    // Rows and columns are 0-based
+    UnequalWidth { row: usize, expected: usize, found: usize },
+    UnknownChar { row: usize, col: usize, chr: char },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::UnequalWidth { row, expected, found } => write!(
+                f,
+                "Row {} has width {}, expected {} like the first row",
+                row, found, expected
+            ),
+            GridError::UnknownChar { row, col, chr } => {
+                write!(f, "Unknown char '{}' at row {}, column {}", chr, row, col)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(nrows: usize, ncols: usize, fill: T) -> Self {
+        Grid {
+            data: vec![fill; nrows * ncols],
+            nrows,
+            ncols,
+            wrap_horizontal: false,
+            wrap_vertical: false,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // Fails if the rows do not all have the same width
+    pub fn from_rows<I: IntoIterator<Item = Vec<T>>>(rows: I) -> Result<Self, GridError> {
+        let mut data = Vec::new();
+        let mut nrows = 0;
+        let mut ncols = 0;
+        for (i, row) in rows.into_iter().enumerate() {
+            if i == 0 {
+                ncols = row.len();
+            } else if row.len() != ncols {
+                return Err(GridError::UnequalWidth { row: i, expected: ncols, found: row.len() });
+            }
+            data.extend(row);
+            nrows += 1;
+        }
+        Ok(Grid { data, nrows, ncols, wrap_horizontal: false, wrap_vertical: false })
+    }
+
+    // Parses one row per line, converting each char to a cell with `f`,
+    // which returns None for chars it does not recognize.
+    pub fn parse<F: Fn(char) -> Option<T>>(string: &str, f: F) -> Result<Self, GridError> {
+        let mut rows = Vec::new();
+        for (row, line) in string.lines().enumerate() {
+            let mut cells = Vec::with_capacity(line.len());
+            for (col, chr) in line.chars().enumerate() {
+                match f(chr) {
+                    Some(cell) => cells.push(cell),
+                    None => return Err(GridError::UnknownChar { row, col, chr }),
+                }
+            }
+            rows.push(cells);
+        }
+        Grid::from_rows(rows)
+    }
+
+    pub fn with_wrapping(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.wrap_horizontal = horizontal;
+        self.wrap_vertical = vertical;
+        self
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.ncols..(row + 1) * self.ncols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // Not data.chunks(ncols), which panics when there are no columns
+        (0..self.nrows).map(move |row| self.row(row))
+    }
+
+    // Index into the data of the cell, wrapping around along the axes that wrap.
+    // Returns None if the position is outside the grid along an axis that doesn't.
+    fn flat_index(&self, row: isize, col: isize) -> Option<usize> {
+        let row = wrap(row, self.nrows, self.wrap_vertical)?;
+        let col = wrap(col, self.ncols, self.wrap_horizontal)?;
+        Some(row * self.ncols + col)
+    }
+
+    pub fn get(&self, row: isize, col: isize) -> Option<&T> {
+        let index = self.flat_index(row, col)?;
+        Some(&self.data[index])
+    }
+
+    pub fn get_mut(&mut self, row: isize, col: isize) -> Option<&mut T> {
+        let index = self.flat_index(row, col)?;
+        Some(&mut self.data[index])
+    }
+}
+
+fn wrap(n: isize, len: usize, wrapping: bool) -> Option<usize> {
+    if len == 0 {
+        None
+    } else if wrapping {
+        Some(n.rem_euclid(len as isize) as usize)
+    } else if n >= 0 && (n as usize) < len {
+        Some(n as usize)
+    } else {
+        None
+    }
+}
+
+// Indexing with (row, col) never wraps, and panics outside the grid
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(row < self.nrows && col < self.ncols, "Index ({}, {}) outside grid", row, col);
+        &self.data[row * self.ncols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(row < self.nrows && col < self.ncols, "Index ({}, {}) outside grid", row, col);
+        &mut self.data[row * self.ncols + col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridError};
+
+    fn parse_cell(chr: char) -> Option<bool> {
+        match chr {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("..#\n#..\n", parse_cell).unwrap();
+        assert_eq!((grid.nrows(), grid.ncols()), (2, 3));
+        assert!(grid[(0, 2)]);
+        assert!(grid[(1, 0)]);
+        assert_eq!(grid.row(1), &[true, false, false]);
+        assert_eq!(grid.rows().count(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Grid::parse("..#\n#.\n", parse_cell),
+            Err(GridError::UnequalWidth { row: 1, expected: 3, found: 2 })
+        );
+        assert_eq!(
+            Grid::parse("..#\n#.x\n", parse_cell),
+            Err(GridError::UnknownChar { row: 1, col: 2, chr: 'x' })
+        );
+        assert!(Grid::parse("", parse_cell).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let grid = Grid::parse("..#\n#..\n", parse_cell).unwrap();
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid.get(-1, 0), None);
+
+        let grid = grid.with_wrapping(true, false);
+        assert_eq!(grid.get(0, 5), Some(&true));
+        assert_eq!(grid.get(1, -3), Some(&true));
+        assert_eq!(grid.get(2, 0), None);
+
+        let mut grid = grid.with_wrapping(false, true);
+        assert_eq!(grid.get(3, 0), Some(&true));
+        assert_eq!(grid.get(0, 3), None);
+        *grid.get_mut(-1, 1).unwrap() = true;
+        assert!(grid[(1, 1)]);
+    }
+}
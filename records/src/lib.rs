@@ -0,0 +1,201 @@
+use std::fmt;
+use std::io::{self, BufRead};
+
+// A group of consecutive non-blank lines. Lines holding only whitespace,
+// including "\r\n", separate records and are never part of one.
+// Line numbers are 1-based and inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record<'a> {
+    pub first: usize,
+    pub last: usize,
+    text: &'a str,
+}
+
+impl<'a> Record<'a> {
+    // The lines without their line endings
+    pub fn lines(&self) -> std::str::Lines<'a> {
+        self.text.lines()
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+}
+
+// Splits a string into records without copying it
+pub fn records(text: &str) -> Records<'_> {
+    Records { rest: text, linenumber: 0 }
+}
+
+pub struct Records<'a> {
+    rest: &'a str,
+    // Number of lines before `rest`
+    linenumber: usize,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = None;
+        let mut end = 0;
+        let mut first = 0;
+        let mut pos = 0;
+        for line in self.rest.split_inclusive('\n') {
+            if line.trim().is_empty() {
+                if start.is_some() {
+                    break;
+                }
+            } else {
+                if start.is_none() {
+                    start = Some(pos);
+                    first = self.linenumber + 1;
+                }
+                end = pos + line.trim_end_matches(&['\r', '\n'][..]).len();
+            }
+            pos += line.len();
+            self.linenumber += 1;
+        }
+        let text = &self.rest[start?..end];
+        self.rest = &self.rest[pos..];
+        Some(Record { first, last: self.linenumber, text })
+    }
+}
+
+// A record that could not be read. If a line is not valid UTF-8, the rest
+// of its record is skipped and reading can go on with the next one.
+// Other I/O errors end the reading.
+#[derive(Debug)]
+pub struct ReadError {
+    pub first: usize,
+    pub last: usize,
+    pub source: io::Error,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "Line {}: {}", self.first, self.source)
+        } else {
+            write!(f, "Lines {}-{}: {}", self.first, self.last, self.source)
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// Reads records one at a time from a stream. Each record borrows from a
+// buffer that is reused for the next one, so this cannot be an `Iterator`:
+//     while let Some(record) = reader.next_record() { ... }
+pub struct RecordReader<R: BufRead> {
+    io: R,
+    // Lines of the current record, separated by "\n"
+    buffer: String,
+    linebuffer: Vec<u8>,
+    linenumber: usize,
+    done: bool,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    pub fn new(io: R) -> RecordReader<R> {
+        RecordReader { io, buffer: String::new(), linebuffer: Vec::new(), linenumber: 0, done: false }
+    }
+
+    pub fn next_record(&mut self) -> Option<Result<Record<'_>, ReadError>> {
+        self.buffer.clear();
+        let mut first = None;
+        let mut error = None;
+        while !self.done {
+            self.linebuffer.clear();
+            match self.io.read_until(b'\n', &mut self.linebuffer) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(_) => self.linenumber += 1,
+                Err(source) => {
+                    self.done = true;
+                    let last = self.linenumber + 1;
+                    return Some(Err(ReadError { first: first.unwrap_or(last), last, source }));
+                }
+            }
+            let line = match std::str::from_utf8(&self.linebuffer) {
+                Ok(line) => line,
+                Err(e) => {
+                    first.get_or_insert(self.linenumber);
+                    error.get_or_insert_with(|| io::Error::new(io::ErrorKind::InvalidData, e));
+                    continue;
+                }
+            };
+            if line.trim().is_empty() {
+                if first.is_some() {
+                    break;
+                }
+                continue;
+            }
+            first.get_or_insert(self.linenumber);
+            if !self.buffer.is_empty() {
+                self.buffer.push('\n');
+            }
+            self.buffer.push_str(line.trim_end_matches(&['\r', '\n'][..]));
+        }
+        let first = first?;
+        // The blank line ending the record is not part of it
+        let last = if self.done { self.linenumber } else { self.linenumber - 1 };
+        Some(match error {
+            Some(source) => Err(ReadError { first, last, source }),
+            None => Ok(Record { first, last, text: &self.buffer }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{records, RecordReader};
+
+    const TEXT: &str = "\r\nabc\r\n\r\na\nb\nc\n \t\n\n\na\r\nb\n\n\nb";
+
+    #[test]
+    fn test_records() {
+        let found: Vec<(usize, usize, Vec<&str>)> =
+            records(TEXT).map(|r| (r.first, r.last, r.lines().collect())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, 2, vec!["abc"]),
+                (4, 6, vec!["a", "b", "c"]),
+                (10, 11, vec!["a", "b"]),
+                (14, 14, vec!["b"]),
+            ]
+        );
+        assert_eq!(records("").count(), 0);
+        assert_eq!(records("\n  \r\n").count(), 0);
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut reader = RecordReader::new(TEXT.as_bytes());
+        let mut found = Vec::new();
+        while let Some(record) = reader.next_record() {
+            let record = record.unwrap();
+            found.push((record.first, record.last, record.text().to_string()));
+        }
+        let expected: Vec<(usize, usize, String)> =
+            records(TEXT).map(|r| (r.first, r.last, r.lines().collect::<Vec<_>>().join("\n"))).collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut reader = RecordReader::new(&b"a\n\xff\nb\n\nc\n"[..]);
+        let error = reader.next_record().unwrap().unwrap_err();
+        assert_eq!((error.first, error.last), (1, 3));
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!((record.first, record.text()), (5, "c"));
+        assert!(reader.next_record().is_none());
+    }
+}